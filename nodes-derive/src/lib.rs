@@ -26,6 +26,8 @@ pub fn derive_from_any(input: proc_macro::TokenStream) -> proc_macro::TokenStrea
                 .map(|field| &field.ty)
                 .collect::<Vec<_>>();
 
+            let indices = 0..types.len();
+
             let is_option = types.iter().map(|t| match t {
                 Type::Path(path) => path.path.segments.last().unwrap().ident == "Option",
                 _ => false,
@@ -47,25 +49,30 @@ pub fn derive_from_any(input: proc_macro::TokenStream) -> proc_macro::TokenStrea
 
             quote::quote! {
                 impl #impl_generics ::nodes::FromAnyProto for #ident #ty_generics #where_clause {
-                    fn from_any(inputs: ::nodes::InputStack<'_, Box<dyn std::any::Any>>) -> Result<Self, ()> {
+                    fn from_any(inputs: ::nodes::InputStack<'_, Box<dyn ::nodes::AnyClone>>) -> Result<Self, ::nodes::InputError> {
                         use ::nodes::InputComponent;
 
                         let required = [#(<#types>::is_optional(),)*];
                         let required_count = required.iter().copied().filter(|v| !*v).count();
 
                         if inputs.as_slice().len() < required_count {
-                            eprintln!("{} < {}", inputs.as_slice().len(), required_count);
-                            return Err(());
+                            return Err(::nodes::InputError::ArityMismatch {
+                                expected: required_count,
+                                found: inputs.as_slice().len(),
+                            });
                         }
 
                         let mut is_optional = required.iter().copied();
                         let mut checker = inputs.deref_iter();
                         #({
                             let is_optional = is_optional.next().unwrap();
-                            let is_type = checker.next().map(|v| <#types>::is(v)).unwrap_or(false);
-                            if !is_optional && !is_type {
-                                eprintln!("Type Mismatch in {}: {}", std::any::type_name::<#ident>(), std::any::type_name::<#types>());
-                                return Err(());
+                            match checker.next() {
+                                Some(v) if is_optional || <#types>::is(v) => {}
+                                Some(v) => {
+                                    return Err(::nodes::InputError::mismatch::<#types>(#indices, v));
+                                }
+                                None if is_optional => {}
+                                None => return Err(::nodes::InputError::MissingInput { index: #indices }),
                             }
                         })*
 
@@ -119,17 +126,27 @@ pub fn derive_from_any(input: proc_macro::TokenStream) -> proc_macro::TokenStrea
 
             let downcasts = all.iter().map(|(variant, field)| {
                 quote::quote! {
-                    if let Ok(v) = <#field as ::nodes::FromAnyProto>::from_any(inputs.sub(..)) {
-                        return Ok(Self::#variant(v));
+                    match <#field as ::nodes::FromAnyProto>::from_any(inputs.sub(..)) {
+                        Ok(v) => return Ok(Self::#variant(v)),
+                        Err(err) => {
+                            error = Some(match error {
+                                Some(prev) => prev.furthest(err),
+                                None => err,
+                            })
+                        }
                     }
                 }
             });
 
             quote::quote! {
                 impl #impl_generics ::nodes::FromAnyProto for #ident #ty_generics #where_clause {
-                    fn from_any(mut inputs: ::nodes::InputStack<'_, Box<dyn std::any::Any>>) -> Result<Self, ()> {
+                    fn from_any(mut inputs: ::nodes::InputStack<'_, Box<dyn ::nodes::AnyClone>>) -> Result<Self, ::nodes::InputError> {
+                        let mut error: Option<::nodes::InputError> = None;
                         #(#downcasts);*
-                        Err(())
+                        Err(error.unwrap_or(::nodes::InputError::ArityMismatch {
+                            expected: 0,
+                            found: inputs.as_slice().len(),
+                        }))
                     }
                     fn possible_inputs(names: &'static [&str]) -> ::nodes::PossibleInputs<'static> {
                         use ::nodes::Itertools;
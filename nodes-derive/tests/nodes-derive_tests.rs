@@ -1,5 +1,5 @@
 use nodes::*;
-use std::any::{Any, TypeId};
+use std::any::TypeId;
 
 #[derive(InputComponent, FromAnyProto, Debug, PartialEq, Clone)]
 struct A {
@@ -35,7 +35,7 @@ struct E {
 
 #[test]
 fn struct_leading_optional_test() {
-    let mut inputs: Vec<Box<dyn Any>> = vec![];
+    let mut inputs: Vec<Box<dyn AnyClone>> = vec![];
     inputs.push(Box::new(Option::<()>::None));
     inputs.push(Box::new(Option::<()>::None));
     inputs.push(Box::new(One::new(3u32)));
@@ -52,7 +52,7 @@ fn struct_leading_optional_test() {
 
 #[test]
 fn struct_skip_optional_test() {
-    let mut inputs: Vec<Box<dyn Any>> = vec![];
+    let mut inputs: Vec<Box<dyn AnyClone>> = vec![];
     inputs.push(Box::new(One::new(2u32)));
     inputs.push(Box::new(Option::<()>::None));
     inputs.push(Box::new(One::new(3u32)));
@@ -79,7 +79,7 @@ fn struct_optional_test() {
     });
     assert_eq!(optional_count, required_count * 2);
 
-    let mut inputs: Vec<Box<dyn Any>> = vec![];
+    let mut inputs: Vec<Box<dyn AnyClone>> = vec![];
     inputs.push(Box::new(One::new(2u32)));
     let d = D::from_any(InputStack::new(&mut inputs, ..)).unwrap();
     assert_eq!(
@@ -135,7 +135,7 @@ fn input_list_enum_test() {
 
 #[test]
 fn from_any_struct_test() {
-    let mut inputs: Vec<Box<dyn Any>> = vec![];
+    let mut inputs: Vec<Box<dyn AnyClone>> = vec![];
     inputs.push(Box::new(One::new(1u32)));
     inputs.push(Box::new(One::new(2u32)));
     let stack = InputStack::new(&mut inputs, ..);
@@ -151,7 +151,7 @@ fn from_any_struct_test() {
 
 #[test]
 fn from_any_enum_test() {
-    let mut inputs: Vec<Box<dyn Any>> = vec![];
+    let mut inputs: Vec<Box<dyn AnyClone>> = vec![];
     inputs.push(Box::new(One::new(1u32)));
     let stack = InputStack::new(&mut inputs, ..);
     let result = B::from_any(stack);
@@ -185,3 +185,50 @@ fn input_component_enum_test() {
     assert!(types_ids.contains(&TypeId::of::<A>()));
     assert!(types_ids.contains(&TypeId::of::<OneOrMany<u32>>()));
 }
+
+#[test]
+fn from_any_errors_test() {
+    let mut inputs: Vec<Box<dyn AnyClone>> = vec![];
+    inputs.push(Box::new(One::new(1u32)));
+    let result = A::from_any(InputStack::new(&mut inputs, ..));
+    assert_eq!(
+        result.err(),
+        Some(InputError::ArityMismatch {
+            expected: 2,
+            found: 1
+        })
+    );
+
+    inputs.push(Box::new(One::new(2f32)));
+    let result = A::from_any(InputStack::new(&mut inputs, ..));
+    assert_eq!(
+        result.err(),
+        Some(InputError::TypeMismatch {
+            index: 1,
+            expected: std::any::type_name::<One<u32>>(),
+            found: std::any::type_name::<One<f32>>(),
+        })
+    );
+
+    let mut inputs: Vec<Box<dyn AnyClone>> = vec![];
+    inputs.push(Box::new(Option::<()>::None));
+    inputs.push(Box::new(Option::<()>::None));
+    inputs.push(Box::new(Option::<()>::None));
+    let result = E::from_any(InputStack::new(&mut inputs, ..));
+    assert_eq!(result.err(), Some(InputError::MissingInput { index: 2 }));
+}
+
+#[test]
+fn from_any_enum_error_test() {
+    let mut inputs: Vec<Box<dyn AnyClone>> = vec![];
+    inputs.push(Box::new(One::new(2f32)));
+    let result = B::from_any(InputStack::new(&mut inputs, ..));
+    assert_eq!(
+        result.err(),
+        Some(InputError::TypeMismatch {
+            index: 0,
+            expected: std::any::type_name::<OneOrMany<u32>>(),
+            found: std::any::type_name::<One<f32>>(),
+        })
+    );
+}
@@ -0,0 +1,218 @@
+use crate::{Error, Graph, InputError, NodeID};
+use slotmap::SecondaryMap;
+use std::fmt::Write;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Scalar {
+    U32,
+    F32,
+    String,
+}
+
+impl Scalar {
+    pub fn name(self) -> &'static str {
+        match self {
+            Scalar::U32 => "u32",
+            Scalar::F32 => "f32",
+            Scalar::String => "String",
+        }
+    }
+}
+
+// Rust source for a node output. Outputs that would be `Many` at runtime are cloneable iterators.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Expr {
+    pub code: String,
+    pub scalar: Scalar,
+    pub many: bool,
+}
+
+impl Expr {
+    pub fn one<S: Into<String>>(scalar: Scalar, code: S) -> Self {
+        Self {
+            code: code.into(),
+            scalar,
+            many: false,
+        }
+    }
+
+    pub fn many<S: Into<String>>(scalar: Scalar, code: S) -> Self {
+        Self {
+            code: code.into(),
+            scalar,
+            many: true,
+        }
+    }
+
+    fn ty(&self) -> String {
+        if self.many {
+            format!("impl Iterator<Item = {}> + Clone", self.scalar.name())
+        } else {
+            self.scalar.name().to_owned()
+        }
+    }
+}
+
+// The connected input at `index`.
+pub fn input(inputs: &[Option<Expr>], index: usize) -> Result<&Expr, InputError> {
+    inputs
+        .get(index)
+        .and_then(Option::as_ref)
+        .ok_or(InputError::MissingInput { index })
+}
+
+pub fn mismatch(index: usize, expected: &'static str, found: &Expr) -> InputError {
+    InputError::TypeMismatch {
+        index,
+        expected,
+        found: found.scalar.name(),
+    }
+}
+
+// `code` as an f32, for a value of the given type.
+pub fn float(code: &str, scalar: Scalar) -> String {
+    match scalar {
+        Scalar::U32 => format!("({} as f32)", code),
+        _ => code.to_owned(),
+    }
+}
+
+pub fn float_literal(v: f32) -> String {
+    if v.is_nan() {
+        "f32::NAN".to_owned()
+    } else if v.is_infinite() && v > 0. {
+        "f32::INFINITY".to_owned()
+    } else if v.is_infinite() {
+        "f32::NEG_INFINITY".to_owned()
+    } else {
+        // debug formatting always includes a decimal point or an exponent
+        format!("{:?}", v)
+    }
+}
+
+// Code that applies `op` to every value of `a`, like `one_many::op1`.
+pub fn op1<F>(a: &Expr, scalar: Scalar, op: F) -> Expr
+where
+    F: Fn(&str) -> String,
+{
+    if a.many {
+        Expr::many(scalar, format!("{}.map(move |v| {})", a.code, op("v")))
+    } else {
+        Expr::one(scalar, op(&a.code))
+    }
+}
+
+// Code that applies `op` to pairs of values, like `one_many::op2`. A single value is paired with
+// every one of many.
+pub fn op2<F>(a: &Expr, b: &Expr, scalar: Scalar, op: F) -> Expr
+where
+    F: Fn(&str, &str) -> String,
+{
+    match (a.many, b.many) {
+        (false, false) => Expr::one(scalar, op(&a.code, &b.code)),
+        (true, false) => Expr::many(
+            scalar,
+            format!("{}.map(move |lhs| {})", a.code, op("lhs", &b.code)),
+        ),
+        (false, true) => Expr::many(
+            scalar,
+            format!("{}.map(move |rhs| {})", b.code, op(&a.code, "rhs")),
+        ),
+        (true, true) => Expr::many(
+            scalar,
+            format!(
+                "{}.zip({}).map(move |(lhs, rhs)| {})",
+                a.code,
+                b.code,
+                op("lhs", "rhs")
+            ),
+        ),
+    }
+}
+
+// Refers to an output that's been bound to a variable. Iterators and strings are cloned since
+// there can be more than one consumer.
+fn reference(var: &str, output: &Expr) -> Expr {
+    let code = if output.many || output.scalar == Scalar::String {
+        format!("{}.clone()", var)
+    } else {
+        var.to_owned()
+    };
+    Expr { code, ..*output }
+}
+
+impl Graph {
+    // Writes a function called `name` that computes the same thing as executing the graph, for
+    // graphs made up of nodes that implement `NodeOutput::codegen`.
+    pub fn to_rust(&self, name: &str) -> Result<String, Error> {
+        let plan = self.compile().map_err(|cycle| Error {
+            executing_node: cycle.nodes[0],
+            inputs: vec![],
+            error: InputError::custom(cycle),
+        })?;
+
+        let mut used: SecondaryMap<NodeID, Vec<usize>> = SecondaryMap::new();
+        used.insert(self.root, vec![0]);
+        for connection in self.connections.iter() {
+            match used.get_mut(connection.from) {
+                Some(outputs) => outputs.push(connection.output),
+                None => {
+                    used.insert(connection.from, vec![connection.output]);
+                }
+            }
+        }
+
+        let mut body = String::new();
+        let mut outputs: Vec<Vec<Option<(String, Expr)>>> = Vec::with_capacity(plan.steps.len());
+        for (index, step) in plan.steps.iter().enumerate() {
+            let node = &self.nodes[step.node];
+            let inputs = step
+                .inputs
+                .iter()
+                .map(|slot| {
+                    let slot = slot.as_ref()?;
+                    let (var, output) = outputs[slot.step].get(slot.output)?.as_ref()?;
+                    Some(reference(var, output))
+                })
+                .collect::<Vec<_>>();
+
+            let count = node.outputs().len();
+            let mut vars = vec![None; count];
+            for output in used.get(step.node).into_iter().flatten().copied() {
+                if output >= count || vars[output].is_some() {
+                    continue;
+                }
+                let expr = node.codegen(output, &inputs).map_err(|error| Error {
+                    executing_node: step.node,
+                    inputs: vec![],
+                    error,
+                })?;
+                let var = if count == 1 {
+                    format!("n{}", index)
+                } else {
+                    format!("n{}_{}", index, output)
+                };
+                if expr.many {
+                    writeln!(body, "    let {} = {};", var, expr.code).unwrap();
+                } else {
+                    let ty = expr.scalar.name();
+                    writeln!(body, "    let {}: {} = {};", var, ty, expr.code).unwrap();
+                }
+                vars[output] = Some((var, expr));
+            }
+            outputs.push(vars);
+        }
+
+        // the root is always the last step
+        let root = outputs
+            .pop()
+            .and_then(|vars| vars.into_iter().next().flatten());
+        let (var, expr) = root.expect("the root has an output");
+        let mut source = String::new();
+        writeln!(source, "pub fn {}() -> {} {{", name, expr.ty()).unwrap();
+        source.push_str(&body);
+        writeln!(source, "    {}", var).unwrap();
+        writeln!(source, "}}").unwrap();
+        Ok(source)
+    }
+}
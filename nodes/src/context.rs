@@ -0,0 +1,68 @@
+use serde::{Deserialize, Serialize};
+
+// The frame rate that lengths in frames are measured at unless a fixed step says otherwise.
+pub const FRAME_RATE: f32 = 60.;
+
+// What a graph is being evaluated for. Nodes take anything that changes from one evaluation to the
+// next from here instead of from global state, so any frame can be evaluated at any time.
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EvalContext {
+    pub frame: u32,
+    // Seconds since the first frame.
+    pub time: f32,
+    // Seconds since the previous frame.
+    pub delta: f32,
+    // How many frames make a second wherever a duration is given in frames. This stays the same
+    // however fast frames are actually drawn.
+    pub frame_rate: f32,
+    // Width and height in pixels of what is being drawn to.
+    pub resolution: [u32; 2],
+    // Nodes that want randomness draw it from this so the same seed always gives the same output.
+    pub seed: u64,
+}
+
+impl EvalContext {
+    // A context where frames are evenly spaced in time, for output that has to come out the same
+    // on every run.
+    pub fn fixed_step(frame: u32, fps: f32) -> Self {
+        Self {
+            delta: 1. / fps,
+            frame_rate: fps,
+            ..Self::default()
+        }
+        .at(frame)
+    }
+
+    // The same context moved to another frame, keeping the step between frames.
+    pub fn at(&self, frame: u32) -> Self {
+        Self {
+            frame,
+            time: frame as f32 * self.delta,
+            ..*self
+        }
+    }
+
+    pub fn with_resolution(self, width: u32, height: u32) -> Self {
+        Self {
+            resolution: [width, height],
+            ..self
+        }
+    }
+
+    pub fn with_seed(self, seed: u64) -> Self {
+        Self { seed, ..self }
+    }
+}
+
+impl Default for EvalContext {
+    fn default() -> Self {
+        Self {
+            frame: 0,
+            time: 0.,
+            delta: 1. / FRAME_RATE,
+            frame_rate: FRAME_RATE,
+            resolution: [0, 0],
+            seed: 0,
+        }
+    }
+}
@@ -0,0 +1,110 @@
+use crate::{ConnectionState, ConstantNode, Graph, Node, NodeID};
+use std::fmt::Write;
+
+// Long text constants, like shader source, are cut short.
+const MAX_VALUE_LENGTH: usize = 32;
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn label(node: &dyn Node) -> String {
+    let value = match node.downcast_ref::<ConstantNode>() {
+        Some(ConstantNode::Unsigned(v)) => v.to_string(),
+        Some(ConstantNode::Float(v)) => v.to_string(),
+        Some(ConstantNode::Text(v)) if v.chars().count() > MAX_VALUE_LENGTH => {
+            let v = v.chars().take(MAX_VALUE_LENGTH).collect::<String>();
+            format!("\"{}...\"", v)
+        }
+        Some(ConstantNode::Text(v)) => format!("\"{}\"", v),
+        None => return node.name().to_owned(),
+    };
+    format!("{}\n{}", node.name(), value)
+}
+
+fn input_name(node: &dyn Node, input: usize) -> String {
+    let index = if node.variadic() { 0 } else { input };
+    let inputs = node.inputs();
+    let info = inputs.groups.iter().find_map(|group| group.info.get(index));
+    match info {
+        Some(info) if node.variadic() => format!("{} {}", info.name, input),
+        Some(info) => info.name.clone().into_owned(),
+        None => input.to_string(),
+    }
+}
+
+fn color(state: ConnectionState) -> &'static str {
+    match state {
+        ConnectionState::Valid => "green",
+        ConnectionState::Invalid => "red",
+        ConnectionState::Ambiguous => "orange",
+        ConnectionState::Unevaluated => "black",
+    }
+}
+
+impl Graph {
+    // Graphviz source for the graph. Connections that haven't been executed are coloured by what
+    // type inference says about them.
+    pub fn to_dot(&self) -> String {
+        self.to_dot_with(|_| None)
+    }
+
+    // Like `to_dot` but with extra attributes for each node, such as its position.
+    pub fn to_dot_with<F>(&self, attributes: F) -> String
+    where
+        F: Fn(NodeID) -> Option<String>,
+    {
+        let inferred = self.infer_types();
+        let mut dot = String::new();
+        writeln!(dot, "digraph {{").unwrap();
+        writeln!(dot, "    rankdir=LR;").unwrap();
+        writeln!(dot, "    node [shape=box];").unwrap();
+        for (id, node) in self.nodes.iter() {
+            let mut line = format!("    \"{:?}\" [label=\"{}\"", id, escape(&label(&**node)));
+            if id == self.root {
+                line.push_str(", peripheries=2");
+            }
+            if let Some(attributes) = attributes(id) {
+                write!(line, ", {}", attributes).unwrap();
+            }
+            writeln!(dot, "{}];", line).unwrap();
+        }
+        for (connection, inferred) in self.connections.iter().zip(inferred) {
+            let to = match self.nodes.get(connection.to) {
+                Some(to) => to,
+                None => continue,
+            };
+            let state = match connection.state {
+                ConnectionState::Unevaluated => inferred,
+                state => state,
+            };
+            let mut line = format!(
+                "    \"{:?}\" -> \"{:?}\" [label=\"{}\", color={}",
+                connection.from,
+                connection.to,
+                escape(&input_name(&**to, connection.input)),
+                color(state)
+            );
+            if let Some(from) = self.nodes.get(connection.from) {
+                let outputs = from.outputs();
+                if outputs.len() > 1 {
+                    if let Some(output) = outputs.get(connection.output) {
+                        write!(line, ", taillabel=\"{}\"", escape(output.name)).unwrap();
+                    }
+                }
+            }
+            writeln!(dot, "{}];", line).unwrap();
+        }
+        writeln!(dot, "}}").unwrap();
+        dot
+    }
+}
@@ -0,0 +1,195 @@
+use crate::{ConnectionState, Graph, Many, Node, NodeID, One};
+use slotmap::SecondaryMap;
+use std::any::TypeId;
+
+// The types a value flowing out of a node might have, worked out without executing anything.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TypeSet {
+    // Nothing is known about the value.
+    Any,
+    OneOf(Vec<TypeId>),
+}
+
+impl TypeSet {
+    pub fn empty() -> Self {
+        TypeSet::OneOf(vec![])
+    }
+
+    pub fn of<T: 'static>() -> Self {
+        TypeSet::OneOf(vec![TypeId::of::<T>()])
+    }
+
+    // What a node built on `one_many::op*` produces for an output of `T`.
+    pub fn one_or_many<T: 'static>() -> Self {
+        TypeSet::OneOf(vec![TypeId::of::<One<T>>(), TypeId::of::<Many<T>>()])
+    }
+
+    pub fn union(self, other: Self) -> Self {
+        match (self, other) {
+            (TypeSet::OneOf(mut lhs), TypeSet::OneOf(rhs)) => {
+                for type_id in rhs {
+                    if !lhs.contains(&type_id) {
+                        lhs.push(type_id);
+                    }
+                }
+                TypeSet::OneOf(lhs)
+            }
+            _ => TypeSet::Any,
+        }
+    }
+
+    pub fn contains(&self, type_id: TypeId) -> bool {
+        match self {
+            TypeSet::Any => true,
+            TypeSet::OneOf(types) => types.contains(&type_id),
+        }
+    }
+
+    pub fn is_subset(&self, other: &Self) -> bool {
+        match (self, other) {
+            (_, TypeSet::Any) => true,
+            (TypeSet::Any, _) => false,
+            (TypeSet::OneOf(types), other) => types.iter().all(|t| other.contains(*t)),
+        }
+    }
+
+    pub fn is_disjoint(&self, other: &Self) -> bool {
+        match (self, other) {
+            (TypeSet::OneOf(types), other) | (other, TypeSet::OneOf(types)) => {
+                !types.iter().any(|t| other.contains(*t))
+            }
+            _ => false,
+        }
+    }
+}
+
+// The types accepted at `input` by each of a node's input groups.
+fn accepted(node: &dyn Node, input: usize) -> Vec<TypeId> {
+    let index = if node.variadic() { 0 } else { input };
+    node.inputs()
+        .groups
+        .iter()
+        .filter_map(|group| group.info.get(index).map(|info| info.type_id))
+        .collect()
+}
+
+fn check(possible: &TypeSet, accepted: &[TypeId]) -> ConnectionState {
+    let accepted = TypeSet::OneOf(accepted.to_vec());
+    if possible.is_disjoint(&accepted) {
+        ConnectionState::Invalid
+    } else if possible.is_subset(&accepted) {
+        ConnectionState::Valid
+    } else {
+        ConnectionState::Ambiguous
+    }
+}
+
+impl Graph {
+    // Works out the state of every connection from the types nodes declare they can produce
+    // and accept, in the same order as `connections`. Nothing is executed.
+    pub fn infer_types(&self) -> Vec<ConnectionState> {
+        self.infer_types_with(&[]).0
+    }
+
+    // Like `infer_types` but with the types of values that will be supplied to inputs from
+    // outside of the graph. Also returns the types of the root's outputs.
+    pub fn infer_types_with(
+        &self,
+        external: &[(NodeID, usize, TypeSet)],
+    ) -> (Vec<ConnectionState>, Vec<TypeSet>) {
+        let mut states = vec![ConnectionState::Ambiguous; self.connections.len()];
+        let mut outputs: SecondaryMap<NodeID, Vec<TypeSet>> = SecondaryMap::new();
+
+        // Kahn's algorithm so that every node is visited, not just those reachable from the root
+        let mut pending: SecondaryMap<NodeID, usize> =
+            self.nodes.keys().map(|id| (id, 0)).collect();
+        for connection in self.connections.iter() {
+            if let Some(count) = pending.get_mut(connection.to) {
+                *count += 1;
+            }
+        }
+        let mut ready = pending
+            .iter()
+            .filter(|(_, count)| **count == 0)
+            .map(|(id, _)| id)
+            .collect::<Vec<_>>();
+
+        while let Some(id) = ready.pop() {
+            let node = &*self.nodes[id];
+            let incoming = self
+                .connections
+                .iter()
+                .enumerate()
+                .filter(|(_, c)| c.to == id)
+                .collect::<Vec<_>>();
+
+            let count = incoming
+                .iter()
+                .map(|(_, c)| c.input + 1)
+                .chain(external.iter().filter(|e| e.0 == id).map(|e| e.1 + 1))
+                .max()
+                .unwrap_or(0);
+            let mut inputs = vec![TypeSet::empty(); count];
+            for (_, input, types) in external.iter().filter(|e| e.0 == id) {
+                inputs[*input] = types.clone();
+            }
+            // connections take precedence over supplied values
+            for (_, c) in incoming.iter() {
+                inputs[c.input] = outputs
+                    .get(c.from)
+                    .and_then(|outputs| outputs.get(c.output))
+                    .cloned()
+                    .unwrap_or(TypeSet::Any);
+            }
+
+            for (index, c) in incoming.iter() {
+                states[*index] = check(&inputs[c.input], &accepted(node, c.input));
+            }
+            // each input might be fine on its own while no single group takes all of them
+            let valid = |(index, _): &(usize, _)| states[*index] == ConnectionState::Valid;
+            let combined = !node.variadic() && !node.independent_inputs();
+            if combined && incoming.iter().all(valid) {
+                let fits = node.inputs().groups.iter().any(|group| {
+                    inputs.iter().enumerate().all(|(index, types)| {
+                        // unconnected inputs don't rule anything out yet
+                        let unconnected = types == &TypeSet::empty();
+                        match group.info.get(index) {
+                            Some(info) => unconnected || types.contains(info.type_id),
+                            None => unconnected,
+                        }
+                    })
+                });
+                if !fits {
+                    for (index, _) in incoming.iter() {
+                        states[*index] = ConnectionState::Ambiguous;
+                    }
+                }
+            }
+
+            let types = (0..node.outputs().len())
+                .map(|output| node.output_types(output, &inputs))
+                .collect();
+            outputs.insert(id, types);
+
+            for c in self.connections.iter().filter(|c| c.from == id) {
+                if let Some(count) = pending.get_mut(c.to) {
+                    *count -= 1;
+                    if *count == 0 {
+                        ready.push(c.to);
+                    }
+                }
+            }
+        }
+
+        let root = outputs.remove(self.root).unwrap_or_default();
+        (states, root)
+    }
+
+    // Marks every connection with its inferred state.
+    pub fn mark_types(&mut self) {
+        let states = self.infer_types();
+        for (connection, state) in self.connections.iter_mut().zip(states) {
+            connection.state = state;
+        }
+    }
+}
@@ -65,10 +65,10 @@ impl<'a, T> InputStack<'a, T> {
 }
 
 pub type DerefIter<'a> = std::iter::Map<
-    std::slice::Iter<'a, Box<dyn std::any::Any>>,
-    fn(&Box<dyn std::any::Any>) -> &dyn std::any::Any,
+    std::slice::Iter<'a, Box<dyn crate::AnyClone>>,
+    fn(&Box<dyn crate::AnyClone>) -> &dyn crate::AnyClone,
 >;
-impl InputStack<'_, Box<dyn std::any::Any>> {
+impl InputStack<'_, Box<dyn crate::AnyClone>> {
     pub fn deref_iter(&self) -> DerefIter<'_> {
         self.as_slice().iter().map(std::ops::Deref::deref)
     }
@@ -157,8 +157,7 @@ mod tests {
 
     #[test]
     fn box_test() {
-        use std::any::Any;
-        let mut stack: Vec<Box<dyn Any>> = vec![Box::new(5), Box::new("x")];
+        let mut stack: Vec<Box<dyn crate::AnyClone>> = vec![Box::new(5), Box::new("x")];
         let substack = InputStack::new(&mut stack, ..);
 
         let count = substack
@@ -1,15 +1,24 @@
 // To allow internal crate references from proc-macro. https://github.com/rust-lang/rust/issues/56409
 extern crate self as nodes;
 
+pub mod codegen;
+mod context;
+mod dot;
+mod inference;
 mod input_stack;
 mod node_impls;
+mod registry;
 
 pub use self::node_impls::*;
+use codegen::Expr;
+pub use context::{EvalContext, FRAME_RATE};
+pub use inference::TypeSet;
 pub use input_stack::*;
 pub use itertools::Itertools;
 pub use nodes_derive::{FromAnyProto, InputComponent};
+pub use registry::{InputDoc, NodeDoc, NodeEntry, Registry};
 use serde::{Deserialize, Serialize};
-use slotmap::SlotMap;
+use slotmap::{SecondaryMap, SlotMap};
 use std::any::{Any, TypeId};
 
 #[derive(Debug, Copy, Clone, Default, Ord, PartialOrd, Eq, PartialEq)]
@@ -33,9 +42,129 @@ impl<T> std::ops::Deref for One<T> {
     }
 }
 
-pub trait ManyTrait<T>: Iterator<Item = T> + dyn_clone::DynClone + std::fmt::Debug {}
+// Node outputs have to be cloneable so that a single evaluation can be handed to every consumer,
+// and Send so that a graph holding on to them can move between threads.
+pub trait AnyClone: Any + dyn_clone::DynClone + Send {
+    fn type_name(&self) -> &'static str;
+}
+dyn_clone::clone_trait_object!(AnyClone);
+impl<T> AnyClone for T
+where
+    T: Any + Clone + Send,
+{
+    fn type_name(&self) -> &'static str {
+        std::any::type_name::<T>()
+    }
+}
+
+impl dyn AnyClone {
+    pub fn is<T: Any>(&self) -> bool {
+        let any: &dyn Any = self;
+        any.is::<T>()
+    }
+
+    pub fn downcast_ref<T: Any>(&self) -> Option<&T> {
+        let any: &dyn Any = self;
+        any.downcast_ref::<T>()
+    }
+
+    pub fn downcast<T: Any>(self: Box<Self>) -> Result<Box<T>, Box<Self>> {
+        if self.is::<T>() {
+            let any: Box<dyn Any> = self;
+            Ok(any.downcast::<T>().unwrap())
+        } else {
+            Err(self)
+        }
+    }
+}
+
+impl std::fmt::Debug for dyn AnyClone {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("AnyClone").field(&self.type_name()).finish()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InputError {
+    ArityMismatch {
+        expected: usize,
+        found: usize,
+    },
+    TypeMismatch {
+        index: usize,
+        expected: &'static str,
+        found: &'static str,
+    },
+    MissingInput {
+        index: usize,
+    },
+    Custom(String),
+}
+
+impl InputError {
+    pub fn custom<T: std::fmt::Display>(msg: T) -> Self {
+        Self::Custom(msg.to_string())
+    }
+
+    // Unconnected inputs are filled with `Option::<()>::None` by the graph.
+    pub fn mismatch<T: ?Sized>(index: usize, found: &dyn AnyClone) -> Self {
+        if found.is::<Option<()>>() {
+            Self::MissingInput { index }
+        } else {
+            Self::TypeMismatch {
+                index,
+                expected: std::any::type_name::<T>(),
+                found: found.type_name(),
+            }
+        }
+    }
+
+    // When several input layouts are attempted, the one that got furthest is the most useful to report.
+    pub fn furthest(self, other: Self) -> Self {
+        fn progress(err: &InputError) -> usize {
+            match err {
+                InputError::ArityMismatch { .. } => 0,
+                InputError::TypeMismatch { index, .. } | InputError::MissingInput { index } => {
+                    index + 1
+                }
+                InputError::Custom(_) => usize::MAX,
+            }
+        }
+
+        if progress(&other) > progress(&self) {
+            other
+        } else {
+            self
+        }
+    }
+}
+
+impl std::fmt::Display for InputError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InputError::ArityMismatch { expected, found } => {
+                write!(f, "expected {} inputs but found {}", expected, found)
+            }
+            InputError::TypeMismatch {
+                index,
+                expected,
+                found,
+            } => write!(
+                f,
+                "input {} expected `{}` but found `{}`",
+                index, expected, found
+            ),
+            InputError::MissingInput { index } => write!(f, "input {} is required", index),
+            InputError::Custom(msg) => f.write_str(msg),
+        }
+    }
+}
+
+impl std::error::Error for InputError {}
+
+pub trait ManyTrait<T>: Iterator<Item = T> + dyn_clone::DynClone + std::fmt::Debug + Send {}
 dyn_clone::clone_trait_object!(<T> ManyTrait<T>);
-impl<I, T> ManyTrait<T> for I where I: Iterator<Item = T> + Clone + std::fmt::Debug {}
+impl<I, T> ManyTrait<T> for I where I: Iterator<Item = T> + Clone + std::fmt::Debug + Send {}
 
 // If it turns out that there are only so many different types of iterator then this could
 // be replaced internally with an enum and the From implementation restricted
@@ -75,7 +204,7 @@ impl<T> Many<T> {
 }
 
 pub trait FromAnyProto {
-    fn from_any(inputs: InputStack<'_, Box<dyn Any>>) -> Result<Self, ()>
+    fn from_any(inputs: InputStack<'_, Box<dyn AnyClone>>) -> Result<Self, InputError>
     where
         Self: Sized;
     fn possible_inputs(names: &'static [&str]) -> PossibleInputs<'static>;
@@ -145,15 +274,19 @@ where
 }
 
 impl<T: 'static> FromAnyProto for One<T> {
-    fn from_any(inputs: InputStack<'_, Box<dyn Any>>) -> Result<Self, ()> {
+    fn from_any(inputs: InputStack<'_, Box<dyn AnyClone>>) -> Result<Self, InputError> {
         if inputs.as_slice().len() == 1 {
-            if One::<T>::is(inputs.deref_iter().next().unwrap()) {
+            let input = inputs.deref_iter().next().unwrap();
+            if One::<T>::is(input) {
                 Ok(One::<T>::downcast(inputs.consume().next().unwrap()).unwrap())
             } else {
-                Err(())
+                Err(InputError::mismatch::<Self>(0, input))
             }
         } else {
-            Err(())
+            Err(InputError::ArityMismatch {
+                expected: 1,
+                found: inputs.as_slice().len(),
+            })
         }
     }
 
@@ -171,15 +304,19 @@ impl<T: 'static> FromAnyProto for One<T> {
 }
 
 impl<T: 'static> FromAnyProto for Many<T> {
-    fn from_any(inputs: InputStack<'_, Box<dyn Any>>) -> Result<Self, ()> {
+    fn from_any(inputs: InputStack<'_, Box<dyn AnyClone>>) -> Result<Self, InputError> {
         if inputs.as_slice().len() == 1 {
-            if Many::<T>::is(inputs.deref_iter().next().unwrap()) {
+            let input = inputs.deref_iter().next().unwrap();
+            if Many::<T>::is(input) {
                 Ok(Many::<T>::downcast(inputs.consume().next().unwrap()).unwrap())
             } else {
-                Err(())
+                Err(InputError::mismatch::<Self>(0, input))
             }
         } else {
-            Err(())
+            Err(InputError::ArityMismatch {
+                expected: 1,
+                found: inputs.as_slice().len(),
+            })
         }
     }
 
@@ -197,13 +334,21 @@ impl<T: 'static> FromAnyProto for Many<T> {
 }
 
 impl<T: 'static> FromAnyProto for OneOrMany<T> {
-    fn from_any(mut inputs: InputStack<'_, Box<dyn Any>>) -> Result<Self, ()> {
+    fn from_any(mut inputs: InputStack<'_, Box<dyn AnyClone>>) -> Result<Self, InputError> {
+        if inputs.as_slice().is_empty() {
+            return Err(InputError::ArityMismatch {
+                expected: 1,
+                found: 0,
+            });
+        }
+
         if let Ok(v) = Many::<T>::from_any(inputs.sub(..1)) {
             Ok(OneOrMany::Many(v))
         } else if let Ok(v) = One::<T>::from_any(inputs.sub(..1)) {
             Ok(OneOrMany::One(v))
         } else {
-            Err(())
+            let input = inputs.deref_iter().next().unwrap();
+            Err(InputError::mismatch::<Self>(0, input))
         }
     }
 
@@ -253,7 +398,7 @@ impl<T: InputComponent + 'static> InputComponent for Option<T> {
 }
 
 impl<T: FromAnyProto + 'static> FromAnyProto for Option<T> {
-    fn from_any(inputs: InputStack<'_, Box<dyn Any>>) -> Result<Self, ()> {
+    fn from_any(inputs: InputStack<'_, Box<dyn AnyClone>>) -> Result<Self, InputError> {
         if inputs.as_slice().len() == 0 {
             Ok(None)
         } else {
@@ -301,8 +446,8 @@ where
     }
 }
 
-impl<T: 'static> OneOrMany<T> {
-    pub fn into_boxed_inner(self) -> Box<dyn Any> {
+impl<T: Clone + Send + 'static> OneOrMany<T> {
+    pub fn into_boxed_inner(self) -> Box<dyn AnyClone> {
         match self {
             OneOrMany::One(inner) => Box::new(inner),
             OneOrMany::Many(inner) => Box::new(inner),
@@ -326,9 +471,9 @@ pub mod one_many {
 
     pub fn op1<A, O, FUNC>(a: OneOrMany<A>, op: FUNC) -> OneOrMany<O>
     where
-        A: Clone + std::fmt::Debug + 'static,
-        O: Clone + std::fmt::Debug + 'static,
-        FUNC: Fn(A) -> O + 'static + Clone,
+        A: Clone + std::fmt::Debug + Send + 'static,
+        O: Clone + std::fmt::Debug + Send + 'static,
+        FUNC: Fn(A) -> O + 'static + Clone + Send,
     {
         match a {
             OneOrMany::One(a) => OneOrMany::One(One(op(a.0))),
@@ -338,10 +483,10 @@ pub mod one_many {
 
     pub fn op2<A, B, O, FUNC>(a: OneOrMany<A>, b: OneOrMany<B>, op: FUNC) -> OneOrMany<O>
     where
-        A: Clone + std::fmt::Debug + 'static,
-        B: Clone + std::fmt::Debug + 'static,
-        O: Clone + std::fmt::Debug + 'static,
-        FUNC: Fn(A, B) -> O + 'static + Clone,
+        A: Clone + std::fmt::Debug + Send + 'static,
+        B: Clone + std::fmt::Debug + Send + 'static,
+        O: Clone + std::fmt::Debug + Send + 'static,
+        FUNC: Fn(A, B) -> O + 'static + Clone + Send,
     {
         match (a, b) {
             (OneOrMany::One(a), OneOrMany::One(b)) => OneOrMany::One(One(op(a.0, b.0))),
@@ -351,10 +496,10 @@ pub mod one_many {
 
     pub fn op2_tuple<A, B, O, FUNC>((a, b): (OneOrMany<A>, OneOrMany<B>), op: FUNC) -> OneOrMany<O>
     where
-        A: Clone + std::fmt::Debug + 'static,
-        B: Clone + std::fmt::Debug + 'static,
-        O: Clone + std::fmt::Debug + 'static,
-        FUNC: Fn(A, B) -> O + 'static + Clone,
+        A: Clone + std::fmt::Debug + Send + 'static,
+        B: Clone + std::fmt::Debug + Send + 'static,
+        O: Clone + std::fmt::Debug + Send + 'static,
+        FUNC: Fn(A, B) -> O + 'static + Clone + Send,
     {
         op2(a, b, op)
     }
@@ -366,11 +511,11 @@ pub mod one_many {
         op: FUNC,
     ) -> OneOrMany<O>
     where
-        A: Clone + std::fmt::Debug + 'static,
-        B: Clone + std::fmt::Debug + 'static,
-        C: Clone + std::fmt::Debug + 'static,
-        O: Clone + std::fmt::Debug + 'static,
-        FUNC: Fn(A, B, C) -> O + 'static + Clone,
+        A: Clone + std::fmt::Debug + Send + 'static,
+        B: Clone + std::fmt::Debug + Send + 'static,
+        C: Clone + std::fmt::Debug + Send + 'static,
+        O: Clone + std::fmt::Debug + Send + 'static,
+        FUNC: Fn(A, B, C) -> O + 'static + Clone + Send,
     {
         match (a, b, c) {
             (OneOrMany::One(a), OneOrMany::One(b), OneOrMany::One(c)) => {
@@ -390,12 +535,12 @@ pub mod one_many {
         op: FUNC,
     ) -> OneOrMany<O>
     where
-        A: Clone + std::fmt::Debug + 'static,
-        B: Clone + std::fmt::Debug + 'static,
-        C: Clone + std::fmt::Debug + 'static,
-        D: Clone + std::fmt::Debug + 'static,
-        O: Clone + std::fmt::Debug + 'static,
-        FUNC: Fn(A, B, C, D) -> O + 'static + Clone,
+        A: Clone + std::fmt::Debug + Send + 'static,
+        B: Clone + std::fmt::Debug + Send + 'static,
+        C: Clone + std::fmt::Debug + Send + 'static,
+        D: Clone + std::fmt::Debug + Send + 'static,
+        O: Clone + std::fmt::Debug + Send + 'static,
+        FUNC: Fn(A, B, C, D) -> O + 'static + Clone + Send,
     {
         match (a, b, c, d) {
             (OneOrMany::One(a), OneOrMany::One(b), OneOrMany::One(c), OneOrMany::One(d)) => {
@@ -419,13 +564,13 @@ pub mod one_many {
         op: FUNC,
     ) -> OneOrMany<O>
     where
-        A: Clone + std::fmt::Debug + 'static,
-        B: Clone + std::fmt::Debug + 'static,
-        C: Clone + std::fmt::Debug + 'static,
-        D: Clone + std::fmt::Debug + 'static,
-        E: Clone + std::fmt::Debug + 'static,
-        O: Clone + std::fmt::Debug + 'static,
-        FUNC: Fn(A, B, C, D, E) -> O + 'static + Clone,
+        A: Clone + std::fmt::Debug + Send + 'static,
+        B: Clone + std::fmt::Debug + Send + 'static,
+        C: Clone + std::fmt::Debug + Send + 'static,
+        D: Clone + std::fmt::Debug + Send + 'static,
+        E: Clone + std::fmt::Debug + Send + 'static,
+        O: Clone + std::fmt::Debug + Send + 'static,
+        FUNC: Fn(A, B, C, D, E) -> O + 'static + Clone + Send,
     {
         match (a, b, c, d, e) {
             (
@@ -444,6 +589,37 @@ pub mod one_many {
             )),
         }
     }
+
+    // Like the fixed arity ops for any number of inputs of the same type.
+    pub fn op_n<A, O, FUNC>(inputs: Vec<OneOrMany<A>>, op: FUNC) -> OneOrMany<O>
+    where
+        A: Clone + std::fmt::Debug + Send + 'static,
+        O: Clone + std::fmt::Debug + Send + 'static,
+        FUNC: Fn(&[A]) -> O + 'static + Clone + Send,
+    {
+        let mut inputs = ZipAll(inputs);
+        if inputs
+            .0
+            .iter()
+            .all(|input| matches!(input, OneOrMany::One(_)))
+        {
+            let values = inputs.next().unwrap();
+            OneOrMany::One(One(op(&values)))
+        } else {
+            OneOrMany::Many(Many::from(inputs.map(move |values| op(&values))))
+        }
+    }
+
+    #[derive(Debug, Clone)]
+    struct ZipAll<A>(Vec<OneOrMany<A>>);
+
+    impl<A: Clone> Iterator for ZipAll<A> {
+        type Item = Vec<A>;
+
+        fn next(&mut self) -> Option<Self::Item> {
+            self.0.iter_mut().map(Iterator::next).collect()
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -467,7 +643,7 @@ impl<'a> PossibleInputs<'a> {
         }
     }
 
-    pub fn best_match<'b>(&'b self, inputs: &[Box<dyn Any>]) -> Option<&'b InputGroup<'a>> {
+    pub fn best_match<'b>(&'b self, inputs: &[Box<dyn AnyClone>]) -> Option<&'b InputGroup<'a>> {
         self.groups
             .iter()
             .max_by(|a, b| a.score(inputs).cmp(&b.score(inputs)))
@@ -481,7 +657,7 @@ pub struct InputGroup<'a> {
 }
 
 impl InputGroup<'_> {
-    pub fn score(&self, inputs: &[Box<dyn Any>]) -> usize {
+    pub fn score(&self, inputs: &[Box<dyn AnyClone>]) -> usize {
         self.info
             .iter()
             .zip(inputs.iter())
@@ -498,7 +674,7 @@ impl InputGroup<'_> {
 
 pub trait NodeInput {
     // fn inputs_match(&self, inputs: &[Box<dyn Any>]) -> Option<InputMatchError>;
-    fn inputs_match(&self, inputs: &[Box<dyn Any>]) -> bool {
+    fn inputs_match(&self, inputs: &[Box<dyn AnyClone>]) -> bool {
         self.inputs().groups.iter().any(|group| {
             group.info.len() == inputs.len()
                 && group
@@ -514,11 +690,59 @@ pub trait NodeInput {
     fn variadic(&self) -> bool {
         false
     }
+    // Each input takes any type that some group accepts there, whatever the others are given.
+    fn independent_inputs(&self) -> bool {
+        false
+    }
     fn inputs(&self) -> PossibleInputs<'static>;
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct OutputInfo {
+    pub name: &'static str,
+    pub ty_name: &'static str,
+}
+
+// Nodes with more than one output return all of them from `op` wrapped in this, in the same
+// order as they are declared by `NodeOutput::outputs`.
+#[derive(Debug, Clone)]
+pub struct Outputs(pub Vec<Box<dyn AnyClone>>);
+
+impl Outputs {
+    // Picks a single output out of a node's result.
+    pub fn select(value: &dyn AnyClone, index: usize) -> Option<Box<dyn AnyClone>> {
+        match value.downcast_ref::<Outputs>() {
+            Some(outputs) => outputs.0.get(index).cloned(),
+            None if index == 0 => Some(dyn_clone::clone_box(value)),
+            None => None,
+        }
+    }
+}
+
 pub trait NodeOutput {
-    fn op(&self, inputs: &mut Vec<Box<dyn Any>>) -> Result<Box<dyn Any>, ()>;
+    fn op(
+        &self,
+        inputs: &mut Vec<Box<dyn AnyClone>>,
+        ctx: &EvalContext,
+    ) -> Result<Box<dyn AnyClone>, InputError>;
+    fn outputs(&self) -> &'static [OutputInfo] {
+        &[OutputInfo {
+            name: "output",
+            ty_name: "any",
+        }]
+    }
+    // The types `output` could produce given the types that could arrive at each input.
+    fn output_types(&self, _output: usize, _inputs: &[TypeSet]) -> TypeSet {
+        TypeSet::Any
+    }
+    // Rust source that computes `output` from the source for each connected input.
+    fn codegen(&self, _output: usize, _inputs: &[Option<Expr>]) -> Result<Expr, InputError> {
+        Err(InputError::custom("this node can't be turned into code"))
+    }
+    // Nodes whose output can change between frames without any of their inputs changing.
+    fn is_time_varying(&self) -> bool {
+        false
+    }
 }
 
 #[typetag::serde(tag = "type")]
@@ -573,6 +797,8 @@ slotmap::new_key_type! {
 pub enum ConnectionState {
     Valid,
     Invalid,
+    // Only some of the types that could arrive are accepted.
+    Ambiguous,
     Unevaluated,
 }
 
@@ -585,6 +811,8 @@ impl Default for ConnectionState {
 #[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Connection {
     pub from: NodeID,
+    #[serde(default)]
+    pub output: usize,
     pub to: NodeID,
     pub input: usize,
     #[serde(skip)]
@@ -600,7 +828,142 @@ impl Connection {
 #[derive(Debug)]
 pub struct Error {
     pub executing_node: NodeID,
-    pub inputs: Vec<Box<dyn Any>>,
+    pub inputs: Vec<Box<dyn AnyClone>>,
+    pub error: InputError,
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?} failed: {}", self.executing_node, self.error)
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+// How a node fared during a best-effort execution.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NodeStatus {
+    Ok,
+    Failed(InputError),
+    // One of its inputs came from a node that failed or was skipped.
+    Skipped,
+}
+
+#[derive(Debug)]
+pub struct Evaluation {
+    // Only present if the root was evaluated successfully.
+    pub output: Option<Box<dyn AnyClone>>,
+    // Every node that the root depends on, including the root itself.
+    pub status: SecondaryMap<NodeID, NodeStatus>,
+}
+
+impl Evaluation {
+    pub fn failed(&self) -> impl Iterator<Item = (NodeID, &InputError)> + '_ {
+        self.status.iter().filter_map(|(id, status)| match status {
+            NodeStatus::Failed(error) => Some((id, error)),
+            _ => None,
+        })
+    }
+}
+
+// The nodes that make up a cycle, in the order that data would flow through them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CycleError {
+    pub nodes: Vec<NodeID>,
+}
+
+impl std::fmt::Display for CycleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "cycle detected between nodes: ")?;
+        for (index, node) in self.nodes.iter().enumerate() {
+            if index > 0 {
+                write!(f, " -> ")?;
+            }
+            write!(f, "{:?}", node)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for CycleError {}
+
+// Why two nodes couldn't be connected.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConnectionError {
+    Cycle(CycleError),
+    MissingNode(NodeID),
+    // The node has fewer outputs than the port asked for.
+    NoSuchOutput {
+        node: NodeID,
+        output: usize,
+        outputs: usize,
+    },
+}
+
+impl std::fmt::Display for ConnectionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConnectionError::Cycle(cycle) => cycle.fmt(f),
+            ConnectionError::MissingNode(node) => write!(f, "node {:?} doesn't exist", node),
+            ConnectionError::NoSuchOutput {
+                node,
+                output,
+                outputs,
+            } => write!(
+                f,
+                "node {:?} has no output {}, only {} output(s)",
+                node, output, outputs
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ConnectionError {}
+
+impl From<CycleError> for ConnectionError {
+    fn from(cycle: CycleError) -> Self {
+        ConnectionError::Cycle(cycle)
+    }
+}
+
+// Where a node's input comes from: the step that produces it and the connection carrying it.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+struct Slot {
+    step: usize,
+    output: usize,
+    connection: usize,
+}
+
+#[derive(Debug, Clone)]
+struct Step {
+    node: NodeID,
+    // unconnected inputs are `None` and get filled with a hole at execution time
+    inputs: Vec<Option<Slot>>,
+}
+
+// The nodes the root depends on in the order they need to be evaluated. Only valid for the graph
+// it was compiled from and only until that graph's nodes or connections change.
+#[derive(Debug, Clone)]
+pub struct ExecutionPlan {
+    steps: Vec<Step>,
+}
+
+impl ExecutionPlan {
+    pub fn nodes(&self) -> impl Iterator<Item = NodeID> + '_ {
+        self.steps.iter().map(|step| step.node)
+    }
+
+    pub fn len(&self) -> usize {
+        self.steps.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -608,6 +971,14 @@ pub struct Graph {
     root: NodeID,
     nodes: SlotMap<NodeID, Box<dyn Node>>,
     connections: Vec<Connection>,
+    #[serde(skip)]
+    plan: Option<ExecutionPlan>,
+    // outputs from previous executions that are still valid
+    #[serde(skip)]
+    cache: SecondaryMap<NodeID, Box<dyn AnyClone>>,
+    // what executions are for, handed to every node
+    #[serde(skip)]
+    context: EvalContext,
 }
 
 impl Graph {
@@ -615,12 +986,19 @@ impl Graph {
     where
         N: Node + 'static,
     {
+        Self::with_boxed_root(Box::new(root))
+    }
+
+    pub fn with_boxed_root(root: Box<dyn Node>) -> Self {
         let mut nodes: SlotMap<NodeID, Box<dyn Node>> = SlotMap::with_key();
-        let root = nodes.insert(Box::new(root));
+        let root = nodes.insert(root);
         Self {
             root,
             nodes,
             connections: vec![],
+            plan: None,
+            cache: SecondaryMap::new(),
+            context: EvalContext::default(),
         }
     }
 
@@ -631,8 +1009,15 @@ impl Graph {
         &self.nodes
     }
     pub fn node_mut(&mut self, id: NodeID) -> Option<&mut dyn Node> {
+        self.invalidate(id);
         self.nodes.get_mut(id).map(Box::as_mut)
     }
+    pub fn replace_node(&mut self, id: NodeID, node: Box<dyn Node>) -> Option<Box<dyn Node>> {
+        let slot = self.nodes.get_mut(id)?;
+        let previous = std::mem::replace(slot, node);
+        self.invalidate(id);
+        Some(previous)
+    }
     pub fn connections(&self) -> &[Connection] {
         self.connections.as_slice()
     }
@@ -645,6 +1030,7 @@ impl Graph {
     }
 
     pub fn add_boxed_node(&mut self, node: Box<dyn Node>) -> NodeID {
+        self.plan = None;
         self.nodes.insert(node)
     }
 
@@ -652,94 +1038,414 @@ impl Graph {
         if id == self.root {
             None
         } else {
+            self.plan = None;
+            self.invalidate(id);
             self.connections.retain(|c| c.from != id && c.to != id);
             self.nodes.remove(id)
         }
     }
 
-    pub fn connect(&mut self, from: NodeID, to: NodeID, input: usize) {
+    pub fn connect(
+        &mut self,
+        from: NodeID,
+        to: NodeID,
+        input: usize,
+    ) -> Result<(), ConnectionError> {
+        self.connect_output(from, 0, to, input)
+    }
+
+    pub fn connect_output(
+        &mut self,
+        from: NodeID,
+        output: usize,
+        to: NodeID,
+        input: usize,
+    ) -> Result<(), ConnectionError> {
+        let outputs = match self.nodes.get(from) {
+            Some(node) => node.outputs().len(),
+            None => return Err(ConnectionError::MissingNode(from)),
+        };
+        if !self.nodes.contains_key(to) {
+            return Err(ConnectionError::MissingNode(to));
+        }
+        // an out of range port would otherwise quietly leave the input unconnected
+        if output >= outputs {
+            return Err(ConnectionError::NoSuchOutput {
+                node: from,
+                output,
+                outputs,
+            });
+        }
+        if let Some(nodes) = self.find_path(to, from) {
+            return Err(CycleError { nodes }.into());
+        }
+
+        self.plan = None;
+        self.invalidate(to);
         let not_same_input = |c: &Connection| c.to != to || c.input != input;
         self.connections.retain(not_same_input);
 
         self.connections.push(Connection {
             from,
+            output,
             to,
             input,
             state: ConnectionState::Unevaluated,
-        })
+        });
+        Ok(())
     }
 
-    pub fn execute(&mut self) -> Result<Box<dyn Any>, Error> {
-        for connection in self.connections.iter_mut() {
-            connection.state = ConnectionState::Unevaluated;
+    pub fn disconnect(&mut self, to: NodeID, input: usize) -> Option<Connection> {
+        let index = self
+            .connections
+            .iter()
+            .position(|c| c.to == to && c.input == input)?;
+        self.plan = None;
+        self.invalidate(to);
+        Some(self.connections.remove(index))
+    }
+
+    pub fn validate(&self) -> Result<(), CycleError> {
+        for connection in self.connections.iter() {
+            if let Some(nodes) = self.find_path(connection.to, connection.from) {
+                return Err(CycleError { nodes });
+            }
         }
-        self.execute_node(self.root)
+        Ok(())
     }
 
-    fn execute_node(&mut self, node_id: NodeID) -> Result<Box<dyn Any>, Error> {
-        let mut connections = self
-            .connections
-            .iter()
-            .filter(|connection| connection.to == node_id)
-            .cloned()
-            .collect::<Vec<_>>();
-        connections.sort_unstable_by(|a, b| a.input.cmp(&b.input));
-        let max = connections.last().map(|c| c.input).unwrap_or(0);
-        let mut inputs = Vec::with_capacity(max);
-        for index in 0..=max {
-            if let Some(connection) = connections.iter().find(|c| c.input == index) {
-                let from = self.nodes.get(connection.from).unwrap();
-                let input = if from.is_terminator() {
-                    let mut inputs = vec![];
-                    from.op(&mut inputs).map_err(|_| Error {
-                        executing_node: connection.from,
-                        inputs,
-                    })
-                } else {
-                    self.execute_node(connection.from)
-                }?;
-                inputs.push(input);
+    // Drops the cached output of `node` and of everything that depends on it.
+    fn invalidate(&mut self, node: NodeID) {
+        let mut visited = SecondaryMap::new();
+        let mut stack = vec![node];
+        while let Some(node) = stack.pop() {
+            if visited.insert(node, ()).is_some() {
+                continue;
+            }
+            self.cache.remove(node);
+            stack.extend(
+                self.connections
+                    .iter()
+                    .filter(|c| c.from == node)
+                    .map(|c| c.to),
+            );
+        }
+    }
+
+    // Follows connections downstream from `start`, returning the nodes visited on the way to `end`.
+    fn find_path(&self, start: NodeID, end: NodeID) -> Option<Vec<NodeID>> {
+        let mut parents = SecondaryMap::new();
+        let mut stack = vec![start];
+        while let Some(node) = stack.pop() {
+            if node == end {
+                let mut path = vec![end];
+                while let Some(&parent) = parents.get(*path.last().unwrap()) {
+                    path.push(parent);
+                }
+                path.reverse();
+                return Some(path);
+            }
+
+            for connection in self.connections.iter().filter(|c| c.from == node) {
+                if connection.to != start && !parents.contains_key(connection.to) {
+                    parents.insert(connection.to, node);
+                    stack.push(connection.to);
+                }
+            }
+        }
+        None
+    }
+
+    pub fn compile(&self) -> Result<ExecutionPlan, CycleError> {
+        let mut incoming: SecondaryMap<NodeID, Vec<usize>> = SecondaryMap::new();
+        for (index, connection) in self.connections.iter().enumerate() {
+            match incoming.get_mut(connection.to) {
+                Some(connections) => connections.push(index),
+                None => {
+                    incoming.insert(connection.to, vec![index]);
+                }
+            }
+        }
+        for (_, connections) in incoming.iter_mut() {
+            connections.sort_by_key(|index| self.connections[*index].input);
+        }
+
+        enum Mark {
+            Visiting,
+            Done(usize),
+        }
+
+        // depth first from the root, a node becomes a step once all of its inputs have
+        let mut steps: Vec<Step> = Vec::with_capacity(self.nodes.len());
+        let mut marks = SecondaryMap::new();
+        let mut stack = vec![(self.root, 0)];
+        marks.insert(self.root, Mark::Visiting);
+        while let Some((node, next)) = stack.last_mut() {
+            let node = *node;
+            let connections = incoming.get(node).map(Vec::as_slice).unwrap_or(&[]);
+            if let Some(&index) = connections.get(*next) {
+                *next += 1;
+                let from = self.connections[index].from;
+                match marks.get(from) {
+                    None => {
+                        marks.insert(from, Mark::Visiting);
+                        stack.push((from, 0));
+                    }
+                    Some(Mark::Visiting) => {
+                        let start = stack.iter().position(|(n, _)| *n == from).unwrap();
+                        let nodes = std::iter::once(from)
+                            .chain(stack[start + 1..].iter().rev().map(|(n, _)| *n))
+                            .collect();
+                        return Err(CycleError { nodes });
+                    }
+                    Some(Mark::Done(_)) => {}
+                }
             } else {
-                // fill the hole
-                inputs.push(Box::new(Option::<()>::None));
+                stack.pop();
+                let max = connections
+                    .last()
+                    .map(|index| self.connections[*index].input)
+                    .unwrap_or(0);
+                let inputs = (0..=max)
+                    .map(|input| {
+                        connections
+                            .iter()
+                            .find(|index| self.connections[**index].input == input)
+                            .map(|&connection| {
+                                let Connection { from, output, .. } = self.connections[connection];
+                                match marks.get(from) {
+                                    Some(Mark::Done(step)) => Slot {
+                                        step: *step,
+                                        output,
+                                        connection,
+                                    },
+                                    _ => unreachable!(),
+                                }
+                            })
+                    })
+                    .collect();
+                marks.insert(node, Mark::Done(steps.len()));
+                steps.push(Step { node, inputs });
             }
         }
-        let to = self.nodes.get(node_id).unwrap();
-        let result = to.op(&mut inputs);
 
-        let mut connections = self
-            .connections
-            .iter_mut()
-            .filter(|connection| connection.to == node_id)
-            .collect::<Vec<_>>();
-        connections.sort_unstable_by(|a, b| a.input.cmp(&b.input));
-        if result.is_ok() {
-            for connection in connections {
-                connection.state = ConnectionState::Valid;
+        Ok(ExecutionPlan { steps })
+    }
+
+    pub fn context(&self) -> &EvalContext {
+        &self.context
+    }
+
+    // Sets what the following executions are for. Nodes can depend on the seed and resolution
+    // without varying over time so cached outputs are dropped when either changes.
+    pub fn set_context(&mut self, context: EvalContext) {
+        if context.seed != self.context.seed || context.resolution != self.context.resolution {
+            self.cache.clear();
+        }
+        self.context = context;
+    }
+
+    pub fn execute(&mut self) -> Result<Box<dyn AnyClone>, Error> {
+        self.execute_with(&[])
+    }
+
+    // Executes for any frame, whatever was executed before.
+    pub fn execute_at(&mut self, context: EvalContext) -> Result<Box<dyn AnyClone>, Error> {
+        self.set_context(context);
+        self.execute()
+    }
+
+    // Executes with values supplied for otherwise unconnected node inputs.
+    pub fn execute_with(
+        &mut self,
+        inputs: &[(NodeID, usize, Box<dyn AnyClone>)],
+    ) -> Result<Box<dyn AnyClone>, Error> {
+        let plan = match self.plan.take() {
+            Some(plan) => plan,
+            None => self.compile().map_err(|cycle| Error {
+                executing_node: cycle.nodes[0],
+                inputs: vec![],
+                error: InputError::custom(cycle),
+            })?,
+        };
+        let result = self.run(&plan, inputs);
+        self.plan = Some(plan);
+        result
+    }
+
+    // Keeps going after a node fails, evaluating every branch that doesn't depend on it.
+    pub fn execute_best_effort(&mut self) -> Evaluation {
+        self.execute_best_effort_with(&[])
+    }
+
+    pub fn execute_best_effort_with(
+        &mut self,
+        inputs: &[(NodeID, usize, Box<dyn AnyClone>)],
+    ) -> Evaluation {
+        let mut status = SecondaryMap::new();
+        let plan = match self.plan.take().map(Ok).unwrap_or_else(|| self.compile()) {
+            Ok(plan) => plan,
+            Err(cycle) => {
+                for connection in self.connections.iter_mut() {
+                    connection.state = ConnectionState::Unevaluated;
+                }
+                let error = InputError::custom(&cycle);
+                for node in cycle.nodes {
+                    status.insert(node, NodeStatus::Failed(error.clone()));
+                }
+                return Evaluation {
+                    output: None,
+                    status,
+                };
             }
-        } else {
-            let possible_inputs = to.inputs();
-            if let Some(best_match) = possible_inputs.best_match(&inputs) {
-                let iter = best_match.info.iter().zip(inputs.iter()).zip(connections);
-                for ((info, input), connection) in iter {
-                    let input = &**input;
-                    let state = if info.type_id == input.type_id() {
-                        ConnectionState::Valid
-                    } else {
-                        ConnectionState::Invalid
-                    };
-                    connection.state = state;
+        };
+        let output = self
+            .evaluate(&plan, inputs, Some(&mut status))
+            .ok()
+            .flatten();
+        self.plan = Some(plan);
+        Evaluation { output, status }
+    }
+
+    fn run(
+        &mut self,
+        plan: &ExecutionPlan,
+        external: &[(NodeID, usize, Box<dyn AnyClone>)],
+    ) -> Result<Box<dyn AnyClone>, Error> {
+        // without somewhere to record failures the first one is returned
+        self.evaluate(plan, external, None)
+            .map(|output| output.expect("the root was evaluated"))
+    }
+
+    fn evaluate(
+        &mut self,
+        plan: &ExecutionPlan,
+        external: &[(NodeID, usize, Box<dyn AnyClone>)],
+        mut status: Option<&mut SecondaryMap<NodeID, NodeStatus>>,
+    ) -> Result<Option<Box<dyn AnyClone>>, Error> {
+        for connection in self.connections.iter_mut() {
+            connection.state = ConnectionState::Unevaluated;
+        }
+
+        // `None` for nodes that failed or were skipped
+        let mut outputs: Vec<Option<Box<dyn AnyClone>>> = Vec::with_capacity(plan.steps.len());
+        let mut evaluated = Vec::with_capacity(plan.steps.len());
+        for step in plan.steps.iter() {
+            let to = self.nodes.get(step.node).unwrap();
+            let mut slots = step.inputs.iter().flatten();
+            if slots.any(|slot| outputs[slot.step].is_none()) {
+                if let Some(status) = status.as_mut() {
+                    status.insert(step.node, NodeStatus::Skipped);
+                }
+                self.cache.remove(step.node);
+                outputs.push(None);
+                evaluated.push(true);
+                continue;
+            }
+
+            let external = external.iter().filter(|(node, ..)| *node == step.node);
+            let stale = to.is_time_varying()
+                || external.clone().next().is_some()
+                || step
+                    .inputs
+                    .iter()
+                    .flatten()
+                    .any(|slot| evaluated[slot.step]);
+            if !stale {
+                if let Some(output) = self.cache.get(step.node) {
+                    for slot in step.inputs.iter().flatten() {
+                        self.connections[slot.connection].state = ConnectionState::Valid;
+                    }
+                    if let Some(status) = status.as_mut() {
+                        status.insert(step.node, NodeStatus::Ok);
+                    }
+                    outputs.push(Some(output.clone()));
+                    evaluated.push(false);
+                    continue;
+                }
+            }
+
+            let mut inputs = step
+                .inputs
+                .iter()
+                .map(|slot| {
+                    slot.and_then(|slot| {
+                        let output = outputs[slot.step].as_ref()?;
+                        Outputs::select(&**output, slot.output)
+                    })
+                    // fill the hole
+                    .unwrap_or_else(|| Box::new(Option::<()>::None))
+                })
+                .collect::<Vec<Box<dyn AnyClone>>>();
+            for (_, input, value) in external {
+                if *input >= inputs.len() {
+                    inputs.resize_with(input + 1, || Box::new(Option::<()>::None));
+                }
+                // connections take precedence over supplied values
+                if step.inputs.get(*input).and_then(Option::as_ref).is_none() {
+                    inputs[*input] = value.clone();
+                }
+            }
+
+            let result = to.op(&mut inputs, &self.context);
+
+            let slots = step.inputs.iter().flatten();
+            if result.is_ok() {
+                for slot in slots {
+                    self.connections[slot.connection].state = ConnectionState::Valid;
                 }
             } else {
-                for connection in connections {
-                    connection.state = ConnectionState::Invalid;
+                let possible_inputs = to.inputs();
+                if let Some(best_match) = possible_inputs.best_match(&inputs) {
+                    let iter = best_match.info.iter().zip(inputs.iter()).zip(&step.inputs);
+                    for ((info, input), slot) in iter {
+                        if let Some(slot) = slot {
+                            let input = &**input;
+                            let state = if info.type_id == input.type_id() {
+                                ConnectionState::Valid
+                            } else {
+                                ConnectionState::Invalid
+                            };
+                            self.connections[slot.connection].state = state;
+                        }
+                    }
+                } else {
+                    for slot in slots {
+                        self.connections[slot.connection].state = ConnectionState::Invalid;
+                    }
                 }
             }
+
+            let result = match (result, status.as_mut()) {
+                (Ok(result), status) => {
+                    if let Some(status) = status {
+                        status.insert(step.node, NodeStatus::Ok);
+                    }
+                    result
+                }
+                (Err(error), Some(status)) => {
+                    self.cache.remove(step.node);
+                    status.insert(step.node, NodeStatus::Failed(error));
+                    outputs.push(None);
+                    evaluated.push(true);
+                    continue;
+                }
+                (Err(error), None) => {
+                    self.cache.remove(step.node);
+                    return Err(Error {
+                        executing_node: step.node,
+                        inputs,
+                        error,
+                    });
+                }
+            };
+            self.cache.insert(step.node, result.clone());
+            outputs.push(Some(result));
+            evaluated.push(true);
         }
-        result.map_err(|_| Error {
-            executing_node: node_id,
-            inputs,
-        })
+
+        // the root is always the last step
+        Ok(outputs.pop().flatten())
     }
 }
 
@@ -859,28 +1565,32 @@ mod tests {
     #[test]
     fn many_one() {
         {
-            let mut buffer: Vec<Box<dyn Any>> = Vec::new();
+            let mut buffer: Vec<Box<dyn AnyClone>> = Vec::new();
             buffer.push(Box::new(One(2u32)));
             buffer.push(Box::new(Into::<Many<u32>>::into(vec![2u32, 3, 4])));
             assert!(MultiplyNode.inputs_match(&buffer));
 
-            let output = MultiplyNode.op(&mut buffer).unwrap();
+            let output = MultiplyNode
+                .op(&mut buffer, &EvalContext::default())
+                .unwrap();
             assert!(buffer.is_empty());
             buffer.push(output);
             buffer.push(Box::new(Into::<Many<u32>>::into(vec![3u32, 4, 5])));
-            let output = MultiplyNode.op(&mut buffer).unwrap();
+            let output = MultiplyNode
+                .op(&mut buffer, &EvalContext::default())
+                .unwrap();
 
             let output = output.downcast::<Many<u32>>().unwrap();
             assert_eq!(vec![12u32, 24, 40], output.collect::<Vec<_>>());
         }
 
         {
-            let mut buffer: Vec<Box<dyn Any>> = Vec::new();
+            let mut buffer: Vec<Box<dyn AnyClone>> = Vec::new();
             buffer.push(Box::new(Into::<Many<u32>>::into(0u32..3)));
             buffer.push(Box::new(One(3u32)));
             assert!(RatioNode.inputs_match(&buffer));
 
-            let output = RatioNode.op(&mut buffer).unwrap();
+            let output = RatioNode.op(&mut buffer, &EvalContext::default()).unwrap();
             assert!(buffer.is_empty());
 
             let output = output.downcast::<Many<f32>>().unwrap();
@@ -888,29 +1598,31 @@ mod tests {
         }
 
         {
-            let mut buffer: Vec<Box<dyn Any>> = vec![];
+            let mut buffer: Vec<Box<dyn AnyClone>> = vec![];
 
             let constant = ConstantNode::Unsigned(3);
             let range = RangeNode;
             let ratio = RatioNode;
             let multiply = MultiplyNode;
 
-            let constant_output = constant.op(&mut buffer).unwrap();
+            let constant_output = constant.op(&mut buffer, &EvalContext::default()).unwrap();
 
             buffer.push(constant_output);
-            let range_output = range.op(&mut buffer).unwrap();
+            let range_output = range.op(&mut buffer, &EvalContext::default()).unwrap();
 
-            let constant_output = constant.op(&mut buffer).unwrap();
+            let constant_output = constant.op(&mut buffer, &EvalContext::default()).unwrap();
 
             buffer.push(range_output);
             buffer.push(constant_output);
-            let ratio_output = ratio.op(&mut buffer).unwrap();
+            let ratio_output = ratio.op(&mut buffer, &EvalContext::default()).unwrap();
 
-            let constant_output = ConstantNode::Float(2.).op(&mut buffer).unwrap();
+            let constant_output = ConstantNode::Float(2.)
+                .op(&mut buffer, &EvalContext::default())
+                .unwrap();
 
             buffer.push(ratio_output);
             buffer.push(constant_output);
-            let multiply_output = multiply.op(&mut buffer).unwrap();
+            let multiply_output = multiply.op(&mut buffer, &EvalContext::default()).unwrap();
 
             let output = multiply_output.downcast::<Many<f32>>().unwrap();
             assert_eq!(
@@ -922,7 +1634,7 @@ mod tests {
         {
             let mut graph = Graph::with_root(RangeNode);
             let constant = graph.add_node(ConstantNode::Unsigned(3));
-            graph.connect(constant, graph.root, 0);
+            graph.connect(constant, graph.root, 0).unwrap();
             let output = graph.execute().unwrap().downcast::<Many<u32>>().unwrap();
             assert_eq!(vec![0, 1, 2], output.collect::<Vec<_>>());
         }
@@ -937,13 +1649,13 @@ mod tests {
             let index = graph.add_node(RangeNode);
             let total = graph.add_node(MultiplyNode);
 
-            graph.connect(width, total, 0);
-            graph.connect(height, total, 1);
+            graph.connect(width, total, 0).unwrap();
+            graph.connect(height, total, 1).unwrap();
 
-            graph.connect(total, index, 0);
+            graph.connect(total, index, 0).unwrap();
 
-            graph.connect(index, graph.root, 0);
-            graph.connect(total, graph.root, 1);
+            graph.connect(index, graph.root, 0).unwrap();
+            graph.connect(total, graph.root, 1).unwrap();
 
             let output = graph.execute().unwrap().downcast::<Many<f32>>().unwrap();
             let control = (0..WIDTH)
@@ -965,4 +1677,563 @@ mod tests {
             assert_eq!(vec![0, 1, 2, 0, 1, 2], output2);
         }
     }
+
+    #[test]
+    fn shared_upstream_evaluated_once() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        static EVALUATIONS: AtomicUsize = AtomicUsize::new(0);
+
+        #[derive(Debug, Clone, Serialize, Deserialize)]
+        struct CountingNode;
+
+        impl NodeInput for CountingNode {
+            fn inputs(&self) -> PossibleInputs<'static> {
+                let groups: &'static [InputGroup] = &[];
+                PossibleInputs::new(groups)
+            }
+        }
+
+        impl NodeOutput for CountingNode {
+            fn op(
+                &self,
+                _inputs: &mut Vec<Box<dyn AnyClone>>,
+                _ctx: &EvalContext,
+            ) -> Result<Box<dyn AnyClone>, InputError> {
+                EVALUATIONS.fetch_add(1, Ordering::SeqCst);
+                Ok(Box::new(One(3u32)))
+            }
+        }
+
+        #[typetag::serde]
+        impl Node for CountingNode {
+            fn name(&self) -> &'static str {
+                "counting"
+            }
+        }
+
+        let mut graph = Graph::with_root(RatioNode);
+        let count = graph.add_node(CountingNode);
+        let range = graph.add_node(RangeNode);
+        graph.connect(count, range, 0).unwrap();
+        graph.connect(range, graph.root, 0).unwrap();
+        graph.connect(count, graph.root, 1).unwrap();
+
+        let output = graph.execute().unwrap().downcast::<Many<f32>>().unwrap();
+        assert_eq!(vec![0., 1. / 3., 2. / 3.], output.collect::<Vec<_>>());
+        assert_eq!(1, EVALUATIONS.load(Ordering::SeqCst));
+
+        // nothing changed so the cached output is reused
+        graph.execute().unwrap();
+        assert_eq!(1, EVALUATIONS.load(Ordering::SeqCst));
+
+        graph.node_mut(count).unwrap();
+        graph.execute().unwrap();
+        assert_eq!(2, EVALUATIONS.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn cached_outputs() {
+        let mut graph = Graph::with_root(MultiplyNode);
+        let constant = graph.add_node(ConstantNode::Unsigned(2));
+        let global = graph.add_node(GlobalNode);
+        graph.connect(constant, graph.root, 0).unwrap();
+        graph.connect(global, graph.root, 1).unwrap();
+
+        let output = graph.execute_at(EvalContext::fixed_step(4, 60.)).unwrap();
+        assert_eq!(8, output.downcast::<One<u32>>().unwrap().inner());
+        assert!(graph.cache.contains_key(constant));
+
+        let output = graph.execute_at(EvalContext::fixed_step(5, 60.)).unwrap();
+        assert_eq!(10, output.downcast::<One<u32>>().unwrap().inner());
+        assert!(graph.cache.contains_key(constant));
+        // an earlier frame is just as easy
+        let output = graph.execute_at(EvalContext::fixed_step(2, 60.)).unwrap();
+        assert_eq!(4, output.downcast::<One<u32>>().unwrap().inner());
+
+        if let Some(node) = graph.node_mut(constant) {
+            *node.downcast_mut::<ConstantNode>().unwrap() = ConstantNode::Unsigned(3);
+        }
+        assert!(!graph.cache.contains_key(constant));
+        assert!(!graph.cache.contains_key(graph.root));
+        let output = graph.execute().unwrap().downcast::<One<u32>>().unwrap();
+        assert_eq!(6, output.inner());
+
+        // the cache goes wherever the graph does, so edits on another thread still clear it
+        let mut graph = std::thread::spawn(move || {
+            graph.replace_node(constant, Box::new(ConstantNode::Unsigned(4)));
+            graph
+        })
+        .join()
+        .unwrap();
+        let output = graph.execute().unwrap().downcast::<One<u32>>().unwrap();
+        assert_eq!(8, output.inner());
+
+        graph.set_context(graph.context().with_seed(1));
+        assert!(!graph.cache.contains_key(constant));
+        graph.execute().unwrap();
+
+        graph.remove_node(global);
+        assert!(graph.cache.contains_key(constant));
+        assert!(!graph.cache.contains_key(graph.root));
+        assert!(graph.execute().is_err());
+    }
+
+    #[test]
+    fn replace_and_disconnect() {
+        let mut graph = Graph::with_root(AddNode);
+        let a = graph.add_node(ConstantNode::Unsigned(2));
+        let b = graph.add_node(ConstantNode::Unsigned(3));
+        graph.connect(a, graph.root, 0).unwrap();
+        graph.connect(b, graph.root, 1).unwrap();
+        let output = graph.execute().unwrap().downcast::<One<u32>>().unwrap();
+        assert_eq!(5, output.inner());
+
+        let previous = graph.replace_node(b, Box::new(ConstantNode::Unsigned(4)));
+        assert!(previous.unwrap().is::<ConstantNode>());
+        assert!(!graph.cache.contains_key(graph.root));
+        let output = graph.execute().unwrap().downcast::<One<u32>>().unwrap();
+        assert_eq!(6, output.inner());
+
+        let removed = graph.disconnect(graph.root, 1).unwrap();
+        assert_eq!((removed.from, removed.input), (b, 1));
+        assert_eq!(graph.disconnect(graph.root, 1), None);
+        assert_eq!(graph.connections().len(), 1);
+        assert!(graph.execute().is_err());
+    }
+
+    #[test]
+    fn cycles() {
+        let mut graph = Graph::with_root(RatioNode);
+        let a = graph.add_node(MultiplyNode);
+        let b = graph.add_node(AddNode);
+        let c = graph.add_node(RangeNode);
+
+        assert_eq!(
+            graph.connect(a, a, 0),
+            Err(CycleError { nodes: vec![a] }.into())
+        );
+
+        graph.connect(a, b, 0).unwrap();
+        graph.connect(b, c, 0).unwrap();
+        graph.connect(c, graph.root, 0).unwrap();
+        assert_eq!(
+            graph.connect(c, a, 1),
+            Err(CycleError {
+                nodes: vec![a, b, c]
+            }
+            .into())
+        );
+        assert_eq!(3, graph.connections().len());
+        assert!(graph.validate().is_ok());
+
+        // replacing an existing input is fine as long as it doesn't loop back
+        graph.connect(a, c, 0).unwrap();
+        assert_eq!(3, graph.connections().len());
+
+        graph.connections.push(Connection {
+            from: graph.root,
+            output: 0,
+            to: a,
+            input: 0,
+            state: ConnectionState::Unevaluated,
+        });
+        let err = graph.validate().unwrap_err();
+        assert_eq!(3, err.nodes.len());
+        assert!(err.nodes.contains(&a));
+        assert!(err.nodes.contains(&c));
+        assert!(err.nodes.contains(&graph.root));
+
+        let err = graph.compile().unwrap_err();
+        assert_eq!(3, err.nodes.len());
+        assert!(graph.execute().is_err());
+    }
+
+    #[test]
+    fn execution_plan() {
+        let mut graph = Graph::with_root(RatioNode);
+        let width = graph.add_node(ConstantNode::Unsigned(3));
+        let height = graph.add_node(ConstantNode::Unsigned(5));
+        let index = graph.add_node(RangeNode);
+        let total = graph.add_node(MultiplyNode);
+        let unused = graph.add_node(ConstantNode::Unsigned(7));
+
+        graph.connect(width, total, 0).unwrap();
+        graph.connect(height, total, 1).unwrap();
+        graph.connect(total, index, 0).unwrap();
+        graph.connect(index, graph.root, 0).unwrap();
+        graph.connect(total, graph.root, 1).unwrap();
+
+        let plan = graph.compile().unwrap();
+        let order = plan.nodes().collect::<Vec<_>>();
+        assert_eq!(vec![width, height, total, index, graph.root], order);
+        assert!(!order.contains(&unused));
+
+        assert!(graph.plan.is_none());
+        graph.execute().unwrap();
+        assert!(graph.plan.is_some());
+        graph.connect(unused, total, 1).unwrap();
+        assert!(graph.plan.is_none());
+        let output = graph.execute().unwrap().downcast::<Many<f32>>().unwrap();
+        assert_eq!(21, output.collect::<Vec<_>>().len());
+    }
+
+    #[test]
+    fn multiple_outputs() {
+        let mut graph = Graph::with_root(MultiplyNode);
+        let width = graph.add_node(ConstantNode::Unsigned(2));
+        let height = graph.add_node(ConstantNode::Unsigned(3));
+        let grid = graph.add_node(GridNode);
+        graph.connect(width, grid, 0).unwrap();
+        graph.connect(height, grid, 1).unwrap();
+        graph.connect_output(grid, 0, graph.root, 0).unwrap();
+        graph.connect_output(grid, 1, graph.root, 1).unwrap();
+
+        let output = graph.execute().unwrap().downcast::<Many<u32>>().unwrap();
+        assert_eq!(vec![0, 0, 0, 1, 0, 2], output.collect::<Vec<_>>());
+        assert!(graph
+            .connections()
+            .iter()
+            .all(|c| c.state == ConnectionState::Valid));
+
+        // ports and nodes that don't exist are refused and leave the graph as it was
+        assert_eq!(
+            graph.connect_output(grid, 2, graph.root, 1),
+            Err(ConnectionError::NoSuchOutput {
+                node: grid,
+                output: 2,
+                outputs: 2
+            })
+        );
+        graph.remove_node(width);
+        assert_eq!(
+            graph.connect(width, grid, 0),
+            Err(ConnectionError::MissingNode(width))
+        );
+        assert_eq!(3, graph.connections().len());
+        assert!(graph.execute().is_err());
+        graph.connect(height, grid, 0).unwrap();
+        assert!(graph.execute().is_ok());
+    }
+
+    #[test]
+    fn subgraph() {
+        let mut inner = Graph::with_root(RatioNode);
+        let range = inner.add_node(RangeNode);
+        inner.connect(range, inner.root(), 0).unwrap();
+        let exposed = vec![
+            ExposedInput {
+                node: range,
+                input: 0,
+                name: "count".into(),
+            },
+            ExposedInput {
+                node: inner.root(),
+                input: 1,
+                name: "denominator".into(),
+            },
+        ];
+        let subgraph = SubgraphNode::new(inner, exposed);
+
+        let inputs = subgraph.inputs();
+        assert!(!inputs.groups.is_empty());
+        for group in inputs.groups.iter() {
+            assert_eq!(2, group.info.len());
+            assert_eq!("count", group.info[0].name);
+            assert_eq!("denominator", group.info[1].name);
+        }
+
+        // exposing lots of inputs doesn't multiply the groups
+        let mut inner = Graph::with_root(AddNode);
+        let mut exposed = vec![];
+        let mut last = inner.root();
+        for index in 0..9 {
+            let add = inner.add_node(AddNode);
+            inner.connect(add, last, 0).unwrap();
+            let name = format!("{}", index);
+            exposed.push(ExposedInput {
+                node: last,
+                input: 1,
+                name,
+            });
+            last = add;
+        }
+        let chain = SubgraphNode::new(inner, exposed);
+        let types = AddNode.inputs().groups.len();
+        assert!(chain.inputs().groups.len() <= types);
+
+        let mut graph = Graph::with_root(subgraph);
+        let count = graph.add_node(ConstantNode::Unsigned(4));
+        graph.connect(count, graph.root, 0).unwrap();
+        graph.connect(count, graph.root, 1).unwrap();
+        let (states, _) = graph.infer_types_with(&[]);
+        assert!(states.iter().all(|state| state == &ConnectionState::Valid));
+        let output = graph.execute().unwrap().downcast::<Many<f32>>().unwrap();
+        assert_eq!(vec![0., 0.25, 0.5, 0.75], output.collect::<Vec<_>>());
+
+        if let Some(node) = graph.node_mut(count) {
+            *node.downcast_mut::<ConstantNode>().unwrap() = ConstantNode::Unsigned(2);
+        }
+        let output = graph.execute().unwrap().downcast::<Many<f32>>().unwrap();
+        assert_eq!(vec![0., 0.5], output.collect::<Vec<_>>());
+
+        // graphs holding subgraphs can still be handed to another thread
+        fn send<T: Send>(_: T) {}
+        send(graph);
+    }
+
+    #[test]
+    fn infer_types() {
+        let mut graph = Graph::with_root(RepeatNode);
+        let count = graph.add_node(ConstantNode::Unsigned(3));
+        let value = graph.add_node(ConstantNode::Float(1.));
+        graph.connect(count, graph.root, 0).unwrap();
+        graph.connect(value, graph.root, 1).unwrap();
+
+        let state = |graph: &Graph, input: usize| {
+            let index = graph
+                .connections()
+                .iter()
+                .position(|c| c.to == graph.root && c.input == input)
+                .unwrap();
+            graph.infer_types()[index]
+        };
+        assert_eq!(ConnectionState::Valid, state(&graph, 0));
+        assert_eq!(ConnectionState::Invalid, state(&graph, 1));
+
+        // addition of unsigned values might give one or many of them
+        let add = graph.add_node(AddNode);
+        graph.connect(count, add, 0).unwrap();
+        graph.connect(count, add, 1).unwrap();
+        graph.connect(add, graph.root, 1).unwrap();
+        assert_eq!(ConnectionState::Valid, state(&graph, 1));
+        graph.connect(add, graph.root, 0).unwrap();
+        assert_eq!(ConnectionState::Ambiguous, state(&graph, 0));
+
+        // nodes that don't lead to the root are checked too
+        let unreachable = graph.add_node(RangeNode);
+        graph.connect(value, unreachable, 0).unwrap();
+        let index = graph
+            .connections()
+            .iter()
+            .position(|c| c.to == unreachable)
+            .unwrap();
+        assert_eq!(ConnectionState::Invalid, graph.infer_types()[index]);
+
+        graph.mark_types();
+        let marked = graph.connections().iter().map(|c| c.state);
+        assert!(marked.eq(graph.infer_types()));
+    }
+
+    #[test]
+    fn best_effort() {
+        let mut graph = Graph::with_root(RepeatNode);
+        let one = graph.add_node(ConstantNode::Unsigned(1));
+        let add = graph.add_node(AddNode);
+        graph.connect(one, add, 0).unwrap();
+        graph.connect(one, add, 1).unwrap();
+        let ratio = graph.add_node(RatioNode);
+        graph.connect(ratio, graph.root, 0).unwrap();
+        graph.connect(add, graph.root, 1).unwrap();
+
+        let error = graph.execute().unwrap_err();
+        assert_eq!(ratio, error.executing_node);
+
+        for _ in 0..2 {
+            let evaluation = graph.execute_best_effort();
+            assert!(evaluation.output.is_none());
+            assert_eq!(Some(&NodeStatus::Ok), evaluation.status.get(one));
+            assert_eq!(Some(&NodeStatus::Ok), evaluation.status.get(add));
+            assert_eq!(
+                Some(&NodeStatus::Skipped),
+                evaluation.status.get(graph.root)
+            );
+            let failed = evaluation.failed().map(|(id, _)| id).collect::<Vec<_>>();
+            assert_eq!(vec![ratio], failed);
+        }
+
+        graph.connect(add, graph.root, 0).unwrap();
+        let evaluation = graph.execute_best_effort();
+        let output = evaluation.output.unwrap().downcast::<Many<u32>>().unwrap();
+        assert_eq!(vec![2, 2], output.collect::<Vec<_>>());
+        assert!(evaluation.status.values().all(|s| s == &NodeStatus::Ok));
+        assert!(!evaluation.status.contains_key(ratio));
+    }
+
+    #[test]
+    fn to_rust() {
+        // the readme's `(0..3).flat_map(|y| (0..3).map(move |x| x + 3 * y))`
+        let mut graph = Graph::with_root(AddNode);
+        let nine = graph.add_node(ConstantNode::Unsigned(9));
+        let three = graph.add_node(ConstantNode::Unsigned(3));
+        let range = graph.add_node(RangeNode);
+        let x = graph.add_node(ModuloNode);
+        let y = graph.add_node(DivisionNode);
+        let offset = graph.add_node(MultiplyNode);
+        graph.connect(nine, range, 0).unwrap();
+        graph.connect(range, x, 0).unwrap();
+        graph.connect(three, x, 1).unwrap();
+        graph.connect(range, y, 0).unwrap();
+        graph.connect(three, y, 1).unwrap();
+        graph.connect(three, offset, 0).unwrap();
+        graph.connect(y, offset, 1).unwrap();
+        graph.connect(x, graph.root, 0).unwrap();
+        graph.connect(offset, graph.root, 1).unwrap();
+
+        let expected = "\
+pub fn grid() -> impl Iterator<Item = u32> + Clone {
+    let n0: u32 = 9;
+    let n1 = 0..n0;
+    let n2: u32 = 3;
+    let n3 = n1.clone().map(move |lhs| lhs.checked_rem(n2).unwrap_or(0));
+    let n4 = n1.clone().map(move |lhs| lhs.checked_div(n2).unwrap_or(0));
+    let n5 = n4.clone().map(move |rhs| n2 * rhs);
+    let n6 = n3.clone().zip(n5.clone()).map(move |(lhs, rhs)| lhs + rhs);
+    n6
+}
+";
+        assert_eq!(expected, graph.to_rust("grid").unwrap());
+        let output = graph.execute().unwrap().downcast::<Many<u32>>().unwrap();
+        assert_eq!((0..9).collect::<Vec<_>>(), output.collect::<Vec<_>>());
+
+        let mut graph = Graph::with_root(SinCosNode);
+        let constant = graph.add_node(ConstantNode::Text("text".into()));
+        graph.connect(constant, graph.root, 0).unwrap();
+        let error = graph.to_rust("text").unwrap_err();
+        assert_eq!(graph.root, error.executing_node);
+
+        graph.add_node(GlobalNode);
+        let global = graph.add_node(GlobalNode);
+        graph.connect(global, graph.root, 0).unwrap();
+        let error = graph.to_rust("global").unwrap_err();
+        assert_eq!(global, error.executing_node);
+    }
+
+    #[test]
+    fn to_dot() {
+        let mut graph = Graph::with_root(AddNode);
+        let one = graph.add_node(ConstantNode::Unsigned(1));
+        let text = graph.add_node(ConstantNode::Text("say \"hi\"".into()));
+        let sin_cos = graph.add_node(SinCosNode);
+        graph.connect(one, graph.root, 0).unwrap();
+        graph.connect(text, sin_cos, 0).unwrap();
+        graph.connect_output(sin_cos, 1, graph.root, 1).unwrap();
+
+        let dot = graph.to_dot();
+        assert!(dot.starts_with("digraph {"));
+        let node = |id: NodeID| {
+            let prefix = format!("    \"{:?}\" [", id);
+            dot.lines().find(|line| line.starts_with(&prefix)).unwrap()
+        };
+        assert!(node(graph.root).contains("label=\"add\", peripheries=2"));
+        assert!(node(one).contains("label=\"constant\\n1\""));
+        assert!(node(text).contains(r#"label="constant\n\"say \"hi\"\"""#));
+
+        let edge = |from: NodeID, to: NodeID| {
+            let prefix = format!("    \"{:?}\" -> \"{:?}\" [", from, to);
+            dot.lines().find(|line| line.starts_with(&prefix)).unwrap()
+        };
+        assert!(edge(one, graph.root).contains("label=\"lhs\""));
+        assert!(edge(text, sin_cos).contains("label=\"number\", color=red"));
+        assert!(edge(sin_cos, graph.root).contains("taillabel=\"cos\""));
+
+        graph.execute().unwrap_err();
+        assert!(graph.to_dot().contains("color=red"));
+    }
+
+    #[test]
+    fn expression() {
+        let mut graph = Graph::with_root(ExpressionNode::new("sin(t) * scale + 1"));
+        let three = graph.add_node(ConstantNode::Unsigned(3));
+        let range = graph.add_node(RangeNode);
+        let scale = graph.add_node(ConstantNode::Float(2.));
+        graph.connect(three, range, 0).unwrap();
+        graph.connect(range, graph.root, 0).unwrap();
+        graph.connect(scale, graph.root, 1).unwrap();
+        let (states, outputs) = graph.infer_types_with(&[]);
+        assert!(states.iter().all(|state| state == &ConnectionState::Valid));
+        assert_eq!(vec![TypeSet::one_or_many::<f32>()], outputs);
+
+        let output = graph.execute().unwrap().downcast::<Many<f32>>().unwrap();
+        let expected = (0..3).map(|t| (t as f32).sin() * 2. + 1.);
+        assert_eq!(expected.collect::<Vec<_>>(), output.collect::<Vec<_>>());
+
+        graph.replace_node(graph.root, Box::new(ExpressionNode::new("scale * 2")));
+        assert_eq!(
+            "scale",
+            graph.nodes[graph.root].inputs().groups[0].info[0].name
+        );
+        graph.disconnect(graph.root, 1);
+        graph.connect(scale, graph.root, 0).unwrap();
+        let output = graph.execute().unwrap().downcast::<One<f32>>().unwrap();
+        assert_eq!(4., output.inner());
+
+        graph.replace_node(graph.root, Box::new(ExpressionNode::new("scale *")));
+        let error = graph.execute().unwrap_err().error;
+        assert_eq!(
+            InputError::custom("expected a value but found the end at position 7"),
+            error
+        );
+    }
+
+    #[test]
+    fn registry() {
+        let mut registry = Registry::builtin();
+        for entry in registry.entries() {
+            let doc = entry.doc();
+            for input in doc.inputs.iter() {
+                assert!(!input.description.is_empty(), "{}.{}", doc.name, input.name);
+            }
+            for (name, _) in entry.inputs.iter() {
+                assert!(doc.inputs.iter().any(|input| input.name == *name));
+            }
+        }
+        assert_eq!(vec!["input", "math", "sequence"], registry.categories());
+
+        let count = registry.len();
+        registry.register(NodeEntry {
+            category: "input",
+            description: "one",
+            inputs: &[],
+            constructor: || Box::new(ConstantNode::Unsigned(1)),
+        });
+        assert_eq!(count, registry.len());
+        assert_eq!("one", registry.get("constant").unwrap().description);
+
+        registry.register(NodeEntry {
+            category: "input",
+            description: "",
+            inputs: &[],
+            constructor: || Box::new(SubgraphNode::new(Graph::with_root(AddNode), vec![])),
+        });
+        assert_eq!("subgraph", registry.entries()[3].name());
+
+        let grid = registry.get("grid").unwrap().doc();
+        assert_eq!(
+            vec!["x", "y"],
+            grid.outputs.iter().map(|o| o.name).collect::<Vec<_>>()
+        );
+        assert!(registry.to_markdown().contains("\n### grid\n"));
+    }
+
+    #[test]
+    fn deep_graph() {
+        const DEPTH: u32 = 5_000;
+
+        let mut graph = Graph::with_root(AddNode);
+        let one = graph.add_node(ConstantNode::Unsigned(1));
+        let mut chain = vec![graph.root];
+        chain.extend((1..DEPTH).map(|_| graph.add_node(AddNode)));
+
+        // connecting from the far end keeps each cycle check short
+        graph.connect(one, chain[chain.len() - 1], 0).unwrap();
+        graph.connect(one, chain[chain.len() - 1], 1).unwrap();
+        for pair in chain.windows(2).rev() {
+            graph.connect(pair[1], pair[0], 0).unwrap();
+            graph.connect(one, pair[0], 1).unwrap();
+        }
+
+        let output = graph.execute().unwrap().downcast::<One<u32>>().unwrap();
+        assert_eq!(DEPTH + 1, output.inner());
+    }
 }
@@ -2,26 +2,38 @@ mod add;
 mod arithmetic;
 mod constant;
 mod division;
+mod expression;
 mod global;
+mod grid;
+mod loop_time;
 mod modulo;
 mod multiply;
+mod noise;
+mod random;
 mod range;
 mod ratio;
 mod repeat;
 mod sin_cos;
+mod subgraph;
 mod to_float;
 
 pub use add::AddNode;
 pub use arithmetic::ArithmeticNodeInput;
 pub use constant::ConstantNode;
 pub use division::DivisionNode;
+pub use expression::{ExpressionNode, ParseError};
 pub use global::GlobalNode;
+pub use grid::GridNode;
+pub use loop_time::LoopTimeNode;
 pub use modulo::ModuloNode;
 pub use multiply::MultiplyNode;
+pub use noise::{FractalNoiseNode, GradientNoiseNode, SimplexNoiseNode, ValueNoiseNode};
+pub use random::{RandomDirectionNode, RandomFloatNode, RandomIntegerNode};
 pub use range::RangeNode;
 pub use ratio::RatioNode;
 pub use repeat::RepeatNode;
-pub use sin_cos::{CosNode, SineNode};
+pub use sin_cos::{CosNode, SinCosNode, SineNode};
+pub use subgraph::{ExposedInput, SubgraphNode};
 pub use to_float::ToFloatNode;
 
 pub mod generic {
@@ -57,16 +69,20 @@ pub mod generic {
             }
 
             impl<$($name: InputComponent),+> crate::FromAnyProto for ($($name,)+) {
-                fn from_any(inputs: crate::InputStack<'_, Box<dyn std::any::Any>>) -> Result<Self, ()> {
+                fn from_any(inputs: crate::InputStack<'_, Box<dyn crate::AnyClone>>) -> Result<Self, crate::InputError> {
                     let len = count_idents!($($name,)+);
                     if inputs.as_slice().len() != len {
-                        return Err(())
+                        return Err(crate::InputError::ArityMismatch {
+                            expected: len,
+                            found: inputs.as_slice().len(),
+                        })
                     }
 
-                    let mut checker = inputs.deref_iter();
+                    let mut checker = inputs.deref_iter().enumerate();
                     $(
-                        if !<$name>::is(checker.next().unwrap()) {
-                            return Err(())
+                        let (index, input) = checker.next().unwrap();
+                        if !<$name>::is(input) {
+                            return Err(crate::InputError::mismatch::<$name>(index, input))
                         }
                     )+
 
@@ -109,8 +125,8 @@ pub mod generic {
 
 #[cfg(test)]
 mod tests {
-    use crate::{FromAnyProto, InputStack, Many, One, OneOrMany};
-    use std::any::{Any, TypeId};
+    use crate::{AnyClone, FromAnyProto, InputError, InputStack, Many, One, OneOrMany};
+    use std::any::TypeId;
 
     #[test]
     fn tuples() {
@@ -132,7 +148,7 @@ mod tests {
         let input_info = <(OneOrMany<u32>, OneOrMany<u32>)>::possible_inputs(&["lhs", "rhs"]);
         assert_eq!(input_info.groups.len(), 9);
 
-        let mut inputs: Vec<Box<dyn Any>> = vec![];
+        let mut inputs: Vec<Box<dyn AnyClone>> = vec![];
         inputs.push(Box::new(One::new(1f32)));
         inputs.push(Box::new(One::new(2f32)));
         let v: (OneOrMany<f32>, OneOrMany<f32>) =
@@ -159,4 +175,35 @@ mod tests {
             v
         );
     }
+
+    #[test]
+    fn tuple_errors() {
+        let mut inputs: Vec<Box<dyn AnyClone>> = vec![Box::new(One::new(1u32))];
+        let err = <(OneOrMany<u32>, OneOrMany<u32>)>::from_any(InputStack::new(&mut inputs, ..))
+            .unwrap_err();
+        assert_eq!(
+            err,
+            InputError::ArityMismatch {
+                expected: 2,
+                found: 1
+            }
+        );
+
+        inputs.push(Box::new(One::new(2f32)));
+        let err = <(OneOrMany<u32>, OneOrMany<u32>)>::from_any(InputStack::new(&mut inputs, ..))
+            .unwrap_err();
+        assert_eq!(
+            err,
+            InputError::TypeMismatch {
+                index: 1,
+                expected: std::any::type_name::<OneOrMany<u32>>(),
+                found: std::any::type_name::<One<f32>>(),
+            }
+        );
+
+        inputs[1] = Box::new(Option::<()>::None);
+        let err = <(OneOrMany<u32>, OneOrMany<u32>)>::from_any(InputStack::new(&mut inputs, ..))
+            .unwrap_err();
+        assert_eq!(err, InputError::MissingInput { index: 1 });
+    }
 }
@@ -1,5 +1,7 @@
-use crate::{FromAnyProto, InputComponent, OneOrMany, PossibleInputs};
-use std::any::Any;
+use crate::codegen::{self, Expr, Scalar};
+use crate::{
+    AnyClone, FromAnyProto, InputComponent, InputError, OneOrMany, PossibleInputs, TypeSet,
+};
 
 #[derive(Debug, Clone, InputComponent, FromAnyProto)]
 pub enum ArithmeticNodeInput {
@@ -22,29 +24,29 @@ impl<A, B> From<(OneOrMany<A>, OneOrMany<B>)> for Pair<A, B> {
 
 impl<A, B> Pair<A, B>
 where
-    A: Clone + std::fmt::Debug + 'static,
-    B: Clone + std::fmt::Debug + 'static,
+    A: Clone + std::fmt::Debug + Send + 'static,
+    B: Clone + std::fmt::Debug + Send + 'static,
 {
     fn op<O, F: Fn(A, B) -> O>(self, op: F) -> OneOrMany<O>
     where
-        O: Clone + std::fmt::Debug + 'static,
-        F: Fn(A, B) -> O + 'static + Clone,
+        O: Clone + std::fmt::Debug + Send + 'static,
+        F: Fn(A, B) -> O + 'static + Clone + Send,
     {
         crate::one_many::op2(self.lhs, self.rhs, op)
     }
 
-    fn opf<O, F: Fn(A, B) -> O>(self, op: F) -> Box<dyn Any>
+    fn opf<O, F: Fn(A, B) -> O>(self, op: F) -> Box<dyn AnyClone>
     where
-        O: Clone + std::fmt::Debug + 'static,
-        F: Fn(A, B) -> O + 'static + Clone,
+        O: Clone + std::fmt::Debug + Send + 'static,
+        F: Fn(A, B) -> O + 'static + Clone + Send,
     {
         self.op(op).into_boxed_inner()
     }
 
     fn op_left<O, F: Fn(A) -> O>(self, op: F) -> Pair<O, B>
     where
-        O: Clone + std::fmt::Debug + 'static,
-        F: Fn(A) -> O + 'static + Clone,
+        O: Clone + std::fmt::Debug + Send + 'static,
+        F: Fn(A) -> O + 'static + Clone + Send,
     {
         let lhs = crate::one_many::op1(self.lhs, op);
         Pair { lhs, rhs: self.rhs }
@@ -52,8 +54,8 @@ where
 
     fn op_right<O, F: Fn(B) -> O>(self, op: F) -> Pair<A, O>
     where
-        O: Clone + std::fmt::Debug + 'static,
-        F: Fn(B) -> O + 'static + Clone,
+        O: Clone + std::fmt::Debug + Send + 'static,
+        F: Fn(B) -> O + 'static + Clone + Send,
     {
         let rhs = crate::one_many::op1(self.rhs, op);
         Pair { lhs: self.lhs, rhs }
@@ -61,7 +63,7 @@ where
 }
 
 impl ArithmeticNodeInput {
-    pub fn mul(self) -> Box<dyn Any> {
+    pub fn mul(self) -> Box<dyn AnyClone> {
         match self {
             ArithmeticNodeInput::F32F32(v) => Pair::from(v).opf(std::ops::Mul::mul),
             ArithmeticNodeInput::U32U32(v) => Pair::from(v).opf(std::ops::Mul::mul),
@@ -74,7 +76,7 @@ impl ArithmeticNodeInput {
         }
     }
 
-    pub fn div(self) -> Box<dyn Any> {
+    pub fn div(self) -> Box<dyn AnyClone> {
         match self {
             ArithmeticNodeInput::F32F32(v) => Pair::from(v).opf(std::ops::Div::div),
             ArithmeticNodeInput::U32U32(v) => {
@@ -89,7 +91,7 @@ impl ArithmeticNodeInput {
         }
     }
 
-    pub fn rem(self) -> Box<dyn Any> {
+    pub fn rem(self) -> Box<dyn AnyClone> {
         match self {
             ArithmeticNodeInput::F32F32(v) => Pair::from(v).opf(std::ops::Rem::rem),
             ArithmeticNodeInput::U32U32(v) => {
@@ -104,7 +106,7 @@ impl ArithmeticNodeInput {
         }
     }
 
-    pub fn add(self) -> Box<dyn Any> {
+    pub fn add(self) -> Box<dyn AnyClone> {
         match self {
             ArithmeticNodeInput::F32F32(v) => Pair::from(v).opf(std::ops::Add::add),
             ArithmeticNodeInput::U32U32(v) => Pair::from(v).opf(std::ops::Add::add),
@@ -117,7 +119,7 @@ impl ArithmeticNodeInput {
         }
     }
 
-    pub fn ratio(self) -> Box<dyn Any> {
+    pub fn ratio(self) -> Box<dyn AnyClone> {
         fn inner(count: f32, length: f32) -> f32 {
             (count % length) / length
         }
@@ -137,6 +139,48 @@ impl ArithmeticNodeInput {
         }
     }
 
+    // Only integers in gives an integer out; anything involving a float is a float.
+    pub fn output_types(inputs: &[TypeSet]) -> TypeSet {
+        let u32s = TypeSet::one_or_many::<u32>();
+        let f32s = TypeSet::one_or_many::<f32>();
+        if inputs.iter().all(|types| types.is_subset(&u32s)) {
+            u32s
+        } else if inputs
+            .iter()
+            .any(|types| types.is_subset(&f32s) && types != &TypeSet::empty())
+        {
+            f32s
+        } else {
+            u32s.union(f32s)
+        }
+    }
+
+    // When both sides are integers `integer` gives the type and code of the result, otherwise both
+    // sides are converted to floats for `float`.
+    pub fn codegen<I, F>(
+        inputs: &[Option<Expr>],
+        integer: (Scalar, I),
+        float: F,
+    ) -> Result<Expr, InputError>
+    where
+        I: Fn(&str, &str) -> String,
+        F: Fn(&str, &str) -> String,
+    {
+        let lhs = codegen::input(inputs, 0)?;
+        let rhs = codegen::input(inputs, 1)?;
+        for (index, side) in [lhs, rhs].iter().enumerate() {
+            if side.scalar == Scalar::String {
+                return Err(codegen::mismatch(index, "u32 or f32", side));
+            }
+        }
+        Ok(match (lhs.scalar, rhs.scalar) {
+            (Scalar::U32, Scalar::U32) => codegen::op2(lhs, rhs, integer.0, integer.1),
+            (l, r) => codegen::op2(lhs, rhs, Scalar::F32, |a, b| {
+                float(&codegen::float(a, l), &codegen::float(b, r))
+            }),
+        })
+    }
+
     pub fn types() -> PossibleInputs<'static> {
         use once_cell::sync::Lazy;
         static CACHE: Lazy<PossibleInputs> =
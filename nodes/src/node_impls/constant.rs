@@ -1,5 +1,5 @@
-use crate::{One, PossibleInputs};
-use std::any::Any;
+use crate::codegen::{self, Expr, Scalar};
+use crate::{AnyClone, InputError, One, PossibleInputs, TypeSet};
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub enum ConstantNode {
@@ -19,13 +19,33 @@ impl crate::NodeInput for ConstantNode {
 }
 
 impl crate::NodeOutput for ConstantNode {
-    fn op(&self, _inputs: &mut Vec<Box<dyn Any>>) -> Result<Box<dyn Any>, ()> {
+    fn op(
+        &self,
+        _inputs: &mut Vec<Box<dyn AnyClone>>,
+        _ctx: &crate::EvalContext,
+    ) -> Result<Box<dyn AnyClone>, InputError> {
         Ok(match self {
             ConstantNode::Unsigned(output) => Box::new(One(*output)),
             ConstantNode::Float(output) => Box::new(One(*output)),
             ConstantNode::Text(output) => Box::new(One(output.clone())),
         })
     }
+
+    fn output_types(&self, _output: usize, _inputs: &[TypeSet]) -> TypeSet {
+        match self {
+            ConstantNode::Unsigned(_) => TypeSet::of::<One<u32>>(),
+            ConstantNode::Float(_) => TypeSet::of::<One<f32>>(),
+            ConstantNode::Text(_) => TypeSet::of::<One<String>>(),
+        }
+    }
+
+    fn codegen(&self, _output: usize, _inputs: &[Option<Expr>]) -> Result<Expr, InputError> {
+        Ok(match self {
+            ConstantNode::Unsigned(v) => Expr::one(Scalar::U32, v.to_string()),
+            ConstantNode::Float(v) => Expr::one(Scalar::F32, codegen::float_literal(*v)),
+            ConstantNode::Text(v) => Expr::one(Scalar::String, format!("String::from({:?})", v)),
+        })
+    }
 }
 
 #[typetag::serde]
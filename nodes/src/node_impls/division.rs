@@ -1,6 +1,6 @@
 use super::arithmetic::ArithmeticNodeInput;
-use crate::{InputStack, PossibleInputs};
-use std::any::Any;
+use crate::codegen::{Expr, Scalar};
+use crate::{AnyClone, InputError, InputStack, PossibleInputs, TypeSet};
 
 #[derive(Debug, Copy, Clone, Default, serde::Serialize, serde::Deserialize)]
 pub struct DivisionNode;
@@ -16,9 +16,27 @@ impl crate::NodeInput for DivisionNode {
 }
 
 impl crate::NodeOutput for DivisionNode {
-    fn op(&self, inputs: &mut Vec<Box<dyn Any>>) -> Result<Box<dyn Any>, ()> {
+    fn op(
+        &self,
+        inputs: &mut Vec<Box<dyn AnyClone>>,
+        _ctx: &crate::EvalContext,
+    ) -> Result<Box<dyn AnyClone>, InputError> {
         crate::FromAnyProto::from_any(InputStack::new(inputs, ..)).map(ArithmeticNodeInput::div)
     }
+
+    fn output_types(&self, _output: usize, inputs: &[TypeSet]) -> TypeSet {
+        ArithmeticNodeInput::output_types(inputs)
+    }
+
+    fn codegen(&self, _output: usize, inputs: &[Option<Expr>]) -> Result<Expr, InputError> {
+        ArithmeticNodeInput::codegen(
+            inputs,
+            (Scalar::U32, |lhs, rhs| {
+                format!("{}.checked_div({}).unwrap_or(0)", lhs, rhs)
+            }),
+            |lhs, rhs| format!("{} / {}", lhs, rhs),
+        )
+    }
 }
 
 #[typetag::serde]
@@ -0,0 +1,518 @@
+use crate::{
+    AnyClone, InputComponent, InputError, InputGroup, InputInfo, Many, One, OneOrMany,
+    PossibleInputs, TypeSet,
+};
+use std::any::{Any, TypeId};
+
+type Function = fn(&[f32]) -> f32;
+
+const FUNCTIONS: &[(&str, usize, Function)] = &[
+    ("sin", 1, |a| a[0].sin()),
+    ("cos", 1, |a| a[0].cos()),
+    ("tan", 1, |a| a[0].tan()),
+    ("asin", 1, |a| a[0].asin()),
+    ("acos", 1, |a| a[0].acos()),
+    ("atan", 1, |a| a[0].atan()),
+    ("atan2", 2, |a| a[0].atan2(a[1])),
+    ("sqrt", 1, |a| a[0].sqrt()),
+    ("abs", 1, |a| a[0].abs()),
+    ("floor", 1, |a| a[0].floor()),
+    ("ceil", 1, |a| a[0].ceil()),
+    ("round", 1, |a| a[0].round()),
+    ("fract", 1, |a| a[0].fract()),
+    ("sign", 1, |a| a[0].signum()),
+    ("exp", 1, |a| a[0].exp()),
+    ("ln", 1, |a| a[0].ln()),
+    ("log2", 1, |a| a[0].log2()),
+    ("log10", 1, |a| a[0].log10()),
+    ("pow", 2, |a| a[0].powf(a[1])),
+    ("min", 2, |a| a[0].min(a[1])),
+    ("max", 2, |a| a[0].max(a[1])),
+    ("clamp", 3, |a| a[0].max(a[1]).min(a[2])),
+    ("mix", 3, |a| a[0] + (a[1] - a[0]) * a[2]),
+];
+
+const CONSTANTS: &[(&str, f32)] = &[
+    ("pi", std::f32::consts::PI),
+    ("tau", std::f32::consts::PI * 2.),
+    ("e", std::f32::consts::E),
+];
+
+// The most arguments any function takes.
+const MAX_ARITY: usize = 3;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    // Byte offset into the expression.
+    pub position: usize,
+    pub message: String,
+}
+
+impl ParseError {
+    fn new<S: Into<String>>(position: usize, message: S) -> Self {
+        Self {
+            position,
+            message: message.into(),
+        }
+    }
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} at position {}", self.message, self.position)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum Token {
+    Number(f32),
+    Identifier(usize, usize),
+    Operator(char),
+    End,
+}
+
+fn tokenize(src: &str) -> Result<Vec<(usize, Token)>, ParseError> {
+    let mut tokens = Vec::new();
+    let mut chars = src.char_indices().peekable();
+    while let Some((start, c)) = chars.next() {
+        let token = if c.is_whitespace() {
+            continue;
+        } else if c.is_ascii_digit() || c == '.' {
+            let mut end = start + 1;
+            while let Some((i, c)) = chars.peek().copied() {
+                if !(c.is_ascii_digit() || c == '.') {
+                    break;
+                }
+                end = i + 1;
+                chars.next();
+            }
+            let number = src[start..end].parse().map_err(|_| {
+                ParseError::new(start, format!("invalid number `{}`", &src[start..end]))
+            })?;
+            Token::Number(number)
+        } else if c.is_alphabetic() || c == '_' {
+            let mut end = start + c.len_utf8();
+            while let Some((i, c)) = chars.peek().copied() {
+                if !(c.is_alphanumeric() || c == '_') {
+                    break;
+                }
+                end = i + c.len_utf8();
+                chars.next();
+            }
+            Token::Identifier(start, end)
+        } else if "+-*/%(),".contains(c) {
+            Token::Operator(c)
+        } else {
+            return Err(ParseError::new(start, format!("unexpected `{}`", c)));
+        };
+        tokens.push((start, token));
+    }
+    tokens.push((src.len(), Token::End));
+    Ok(tokens)
+}
+
+#[derive(Debug, Clone)]
+enum Ast {
+    Number(f32),
+    Variable(usize),
+    Negate(Box<Ast>),
+    Binary(char, Box<Ast>, Box<Ast>),
+    Call(Function, Vec<Ast>),
+}
+
+impl Ast {
+    fn eval(&self, variables: &[f32]) -> f32 {
+        match self {
+            Ast::Number(v) => *v,
+            Ast::Variable(index) => variables[*index],
+            Ast::Negate(v) => -v.eval(variables),
+            Ast::Binary(op, lhs, rhs) => {
+                let (lhs, rhs) = (lhs.eval(variables), rhs.eval(variables));
+                match op {
+                    '+' => lhs + rhs,
+                    '-' => lhs - rhs,
+                    '*' => lhs * rhs,
+                    '/' => lhs / rhs,
+                    _ => lhs % rhs,
+                }
+            }
+            Ast::Call(function, args) => {
+                let mut values = [0.; MAX_ARITY];
+                for (value, arg) in values.iter_mut().zip(args) {
+                    *value = arg.eval(variables);
+                }
+                function(&values[..args.len()])
+            }
+        }
+    }
+}
+
+struct Parser<'a> {
+    src: &'a str,
+    tokens: Vec<(usize, Token)>,
+    next: usize,
+    variables: Vec<String>,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> (usize, Token) {
+        self.tokens[self.next]
+    }
+
+    fn advance(&mut self) -> (usize, Token) {
+        let token = self.peek();
+        if token.1 != Token::End {
+            self.next += 1;
+        }
+        token
+    }
+
+    fn eat(&mut self, op: char) -> bool {
+        if self.peek().1 == Token::Operator(op) {
+            self.advance();
+            true
+        } else {
+            false
+        }
+    }
+
+    // Consumes the next token if it's one of `ops`.
+    fn operator(&mut self, ops: &str) -> Option<char> {
+        match self.peek().1 {
+            Token::Operator(op) if ops.contains(op) => {
+                self.advance();
+                Some(op)
+            }
+            _ => None,
+        }
+    }
+
+    fn expect(&mut self, op: char) -> Result<(), ParseError> {
+        if self.eat(op) {
+            Ok(())
+        } else {
+            Err(self.unexpected(&format!("`{}`", op)))
+        }
+    }
+
+    fn unexpected(&self, expected: &str) -> ParseError {
+        let (position, token) = self.peek();
+        let found = match token {
+            Token::End => "the end".to_owned(),
+            Token::Number(_) => "a number".to_owned(),
+            Token::Identifier(start, end) => format!("`{}`", &self.src[start..end]),
+            Token::Operator(op) => format!("`{}`", op),
+        };
+        ParseError::new(
+            position,
+            format!("expected {} but found {}", expected, found),
+        )
+    }
+
+    // sum := product (('+' | '-') product)*
+    fn sum(&mut self) -> Result<Ast, ParseError> {
+        let mut lhs = self.product()?;
+        while let Some(op) = self.operator("+-") {
+            lhs = Ast::Binary(op, Box::new(lhs), Box::new(self.product()?));
+        }
+        Ok(lhs)
+    }
+
+    // product := unary (('*' | '/' | '%') unary)*
+    fn product(&mut self) -> Result<Ast, ParseError> {
+        let mut lhs = self.unary()?;
+        while let Some(op) = self.operator("*/%") {
+            lhs = Ast::Binary(op, Box::new(lhs), Box::new(self.unary()?));
+        }
+        Ok(lhs)
+    }
+
+    // unary := '-' unary | atom
+    fn unary(&mut self) -> Result<Ast, ParseError> {
+        if self.eat('-') {
+            Ok(Ast::Negate(Box::new(self.unary()?)))
+        } else {
+            self.atom()
+        }
+    }
+
+    // atom := number | constant | variable | function '(' sum (',' sum)* ')' | '(' sum ')'
+    fn atom(&mut self) -> Result<Ast, ParseError> {
+        match self.peek().1 {
+            Token::Number(v) => {
+                self.advance();
+                Ok(Ast::Number(v))
+            }
+            Token::Operator('(') => {
+                self.advance();
+                let inner = self.sum()?;
+                self.expect(')')?;
+                Ok(inner)
+            }
+            Token::Identifier(start, end) => {
+                self.advance();
+                let name = &self.src[start..end];
+                let function = FUNCTIONS.iter().find(|(f, _, _)| *f == name);
+                if let Some((_, arity, function)) = function {
+                    self.expect('(')?;
+                    let mut args = vec![self.sum()?];
+                    while self.eat(',') {
+                        args.push(self.sum()?);
+                    }
+                    self.expect(')')?;
+                    if args.len() != *arity {
+                        let message = format!(
+                            "`{}` takes {} argument(s) but was given {}",
+                            name,
+                            arity,
+                            args.len()
+                        );
+                        return Err(ParseError::new(start, message));
+                    }
+                    Ok(Ast::Call(*function, args))
+                } else if self.peek().1 == Token::Operator('(') {
+                    Err(ParseError::new(
+                        start,
+                        format!("unknown function `{}`", name),
+                    ))
+                } else if let Some((_, v)) = CONSTANTS.iter().find(|(c, _)| *c == name) {
+                    Ok(Ast::Number(*v))
+                } else {
+                    let index = match self.variables.iter().position(|v| v == name) {
+                        Some(index) => index,
+                        None => {
+                            self.variables.push(name.to_owned());
+                            self.variables.len() - 1
+                        }
+                    };
+                    Ok(Ast::Variable(index))
+                }
+            }
+            _ => Err(self.unexpected("a value")),
+        }
+    }
+}
+
+// The expression and its free variables, in the order they first appear.
+fn parse(src: &str) -> Result<(Ast, Vec<String>), ParseError> {
+    let mut parser = Parser {
+        src,
+        tokens: tokenize(src)?,
+        next: 0,
+        variables: vec![],
+    };
+    let ast = parser.sum()?;
+    if parser.peek().1 != Token::End {
+        return Err(parser.unexpected("an operator"));
+    }
+    Ok((ast, parser.variables))
+}
+
+#[derive(Clone, InputComponent)]
+enum Value {
+    F32(OneOrMany<f32>),
+    U32(OneOrMany<u32>),
+}
+
+impl Value {
+    fn into_f32(self) -> OneOrMany<f32> {
+        match self {
+            Value::F32(v) => v,
+            Value::U32(v) => crate::one_many::op1(v, |v| v as f32),
+        }
+    }
+}
+
+type Parsed = Result<(Ast, Vec<String>), ParseError>;
+
+// A formula over its inputs, e.g. `sin(t * 0.1) * 200 + 400`. Each free variable becomes an input
+// that takes numbers, and the result is always an f32.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ExpressionNode {
+    expression: String,
+    // parsed the first time it's needed rather than on every execution
+    #[serde(skip)]
+    parsed: once_cell::sync::OnceCell<Parsed>,
+}
+
+impl ExpressionNode {
+    pub fn new<S: Into<String>>(expression: S) -> Self {
+        Self {
+            expression: expression.into(),
+            parsed: Default::default(),
+        }
+    }
+
+    pub fn expression(&self) -> &str {
+        &self.expression
+    }
+
+    pub fn variables(&self) -> Result<&[String], ParseError> {
+        self.parsed().map(|(_, variables)| variables)
+    }
+
+    fn parsed(&self) -> Result<(&Ast, &[String]), ParseError> {
+        match self.parsed.get_or_init(|| parse(&self.expression)) {
+            Ok((ast, variables)) => Ok((ast, variables)),
+            Err(err) => Err(err.clone()),
+        }
+    }
+}
+
+impl Default for ExpressionNode {
+    fn default() -> Self {
+        Self::new("x")
+    }
+}
+
+impl crate::NodeInput for ExpressionNode {
+    // Every variable takes any of the number types, so there's one group per type rather than
+    // one for every combination of them.
+    fn independent_inputs(&self) -> bool {
+        true
+    }
+
+    fn inputs(&self) -> PossibleInputs<'static> {
+        // a broken expression keeps whatever is connected until it's fixed
+        let variables = self.variables().unwrap_or_default();
+        let types = [
+            (TypeId::of::<One<f32>>(), "f32"),
+            (TypeId::of::<Many<f32>>(), "f32"),
+            (TypeId::of::<One<u32>>(), "u32"),
+            (TypeId::of::<Many<u32>>(), "u32"),
+        ];
+        let groups = types
+            .iter()
+            .map(|&(type_id, ty_name)| InputGroup {
+                info: variables
+                    .iter()
+                    .map(|name| InputInfo {
+                        name: name.clone().into(),
+                        ty_name,
+                        type_id,
+                        optional: false,
+                    })
+                    .collect(),
+            })
+            .collect::<Vec<_>>();
+        PossibleInputs::new(groups)
+    }
+}
+
+impl crate::NodeOutput for ExpressionNode {
+    fn op(
+        &self,
+        inputs: &mut Vec<Box<dyn AnyClone>>,
+        _ctx: &crate::EvalContext,
+    ) -> Result<Box<dyn AnyClone>, InputError> {
+        let (ast, variables) = self.parsed().map_err(InputError::custom)?;
+        if inputs.len() < variables.len() {
+            return Err(InputError::MissingInput {
+                index: inputs.len(),
+            });
+        }
+        let values = inputs
+            .drain(..)
+            .take(variables.len())
+            .enumerate()
+            .map(|(index, input)| {
+                if Value::is(input.as_ref() as &dyn Any) {
+                    let input: Box<dyn Any> = input;
+                    Ok(Value::downcast(input).ok().unwrap().into_f32())
+                } else {
+                    Err(InputError::mismatch::<Value>(index, &*input))
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let ast = ast.clone();
+        let op = move |values: &[f32]| ast.eval(values);
+        Ok(crate::one_many::op_n(values, op).into_boxed_inner())
+    }
+
+    fn output_types(&self, _output: usize, inputs: &[TypeSet]) -> TypeSet {
+        let many = TypeSet::OneOf(vec![TypeId::of::<Many<f32>>(), TypeId::of::<Many<u32>>()]);
+        if inputs.iter().any(|types| !types.is_disjoint(&many)) {
+            TypeSet::one_or_many::<f32>()
+        } else {
+            TypeSet::of::<One<f32>>()
+        }
+    }
+}
+
+#[typetag::serde]
+impl crate::Node for ExpressionNode {
+    fn name(&self) -> &'static str {
+        "expression"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn eval(src: &str, variables: &[f32]) -> f32 {
+        let (ast, _) = parse(src).unwrap();
+        ast.eval(variables)
+    }
+
+    #[test]
+    fn precedence() {
+        assert_eq!(7., eval("1 + 2 * 3", &[]));
+        assert_eq!(9., eval("(1 + 2) * 3", &[]));
+        assert_eq!(-1., eval("1 - 2", &[]));
+        assert_eq!(0., eval("1 - 2 + 1", &[]));
+        assert_eq!(1., eval("7 % 3", &[]));
+        assert_eq!(-4., eval("-2 * 2", &[]));
+        assert_eq!(2., eval("--2", &[]));
+        assert_eq!(std::f32::consts::PI, eval("pi", &[]));
+        assert_eq!(3., eval("max(1, min(3, 4))", &[]));
+        assert_eq!(0.5, eval("clamp(x, 0, 1) / 2", &[3.]));
+    }
+
+    #[test]
+    fn variables() {
+        let (ast, variables) = parse("sin(t * 0.1) * amplitude + t").unwrap();
+        assert_eq!(vec!["t", "amplitude"], variables);
+        assert_eq!(0. * 200. + 0., ast.eval(&[0., 200.]));
+    }
+
+    #[test]
+    fn inputs() {
+        use crate::NodeInput;
+        let node = ExpressionNode::new("a + b + c + d + f + g + h + j + k");
+        let inputs = node.inputs();
+        assert_eq!(4, inputs.groups.len());
+        assert!(inputs.groups.iter().all(|group| group.info.len() == 9));
+        let broken = ExpressionNode::new("1 +").inputs();
+        assert!(broken.groups.iter().all(|group| group.info.is_empty()));
+    }
+
+    #[test]
+    fn errors() {
+        let error = |src| parse(src).map(|_| ()).unwrap_err();
+        assert_eq!(
+            error("1 +"),
+            ParseError::new(3, "expected a value but found the end")
+        );
+        assert_eq!(
+            error("(1"),
+            ParseError::new(2, "expected `)` but found the end")
+        );
+        assert_eq!(
+            error("1 2"),
+            ParseError::new(2, "expected an operator but found a number")
+        );
+        assert_eq!(
+            error("foo(1)"),
+            ParseError::new(0, "unknown function `foo`")
+        );
+        assert_eq!(error("x $ 1"), ParseError::new(2, "unexpected `$`"));
+        assert_eq!(error("1.2.3"), ParseError::new(0, "invalid number `1.2.3`"));
+        assert_eq!(
+            error("pow(2)"),
+            ParseError::new(0, "`pow` takes 2 argument(s) but was given 1")
+        );
+    }
+}
@@ -1,22 +1,9 @@
-use crate::{One, PossibleInputs};
-use std::any::Any;
-use std::sync::atomic::{AtomicU32, Ordering};
-
-static TICK: AtomicU32 = AtomicU32::new(1);
+use crate::{AnyClone, InputError, One, PossibleInputs, TypeSet};
 
+// The frame being evaluated.
 #[derive(Debug, Copy, Clone, serde::Serialize, serde::Deserialize)]
 pub struct GlobalNode;
 
-impl GlobalNode {
-    pub fn incr() -> u32 {
-        TICK.fetch_add(1, Ordering::SeqCst)
-    }
-
-    pub fn load() -> u32 {
-        TICK.load(Ordering::SeqCst)
-    }
-}
-
 impl crate::NodeInput for GlobalNode {
     fn is_terminator(&self) -> bool {
         true
@@ -28,8 +15,20 @@ impl crate::NodeInput for GlobalNode {
 }
 
 impl crate::NodeOutput for GlobalNode {
-    fn op(&self, _inputs: &mut Vec<Box<dyn Any>>) -> Result<Box<dyn Any>, ()> {
-        Ok(Box::new(One::new(Self::load())))
+    fn op(
+        &self,
+        _inputs: &mut Vec<Box<dyn AnyClone>>,
+        ctx: &crate::EvalContext,
+    ) -> Result<Box<dyn AnyClone>, InputError> {
+        Ok(Box::new(One::new(ctx.frame)))
+    }
+
+    fn output_types(&self, _output: usize, _inputs: &[TypeSet]) -> TypeSet {
+        TypeSet::of::<One<u32>>()
+    }
+
+    fn is_time_varying(&self) -> bool {
+        true
     }
 }
 
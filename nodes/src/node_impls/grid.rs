@@ -0,0 +1,66 @@
+use crate::{
+    AnyClone, FromAnyProto, InputComponent, InputError, InputStack, Many, One, OutputInfo, Outputs,
+    PossibleInputs, TypeSet,
+};
+
+#[derive(FromAnyProto, InputComponent)]
+struct GridNodeInput {
+    width: One<u32>,
+    height: One<u32>,
+}
+
+impl GridNodeInput {
+    fn op(self) -> Box<dyn AnyClone> {
+        let width = self.width.inner();
+        let height = self.height.inner();
+        let x = Many::from((0..height).flat_map(move |_| 0..width));
+        let y = Many::from((0..height).flat_map(move |y| (0..width).map(move |_| y)));
+        Box::new(Outputs(vec![Box::new(x), Box::new(y)]))
+    }
+}
+
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+pub struct GridNode;
+
+impl crate::NodeInput for GridNode {
+    fn inputs(&self) -> PossibleInputs<'static> {
+        use once_cell::sync::Lazy;
+        static CACHE: Lazy<PossibleInputs> =
+            Lazy::new(|| GridNodeInput::possible_inputs(&["width", "height"]));
+        PossibleInputs::new(&*CACHE.groups)
+    }
+}
+
+impl crate::NodeOutput for GridNode {
+    fn op(
+        &self,
+        inputs: &mut Vec<Box<dyn AnyClone>>,
+        _ctx: &crate::EvalContext,
+    ) -> Result<Box<dyn AnyClone>, InputError> {
+        FromAnyProto::from_any(InputStack::new(inputs, ..)).map(GridNodeInput::op)
+    }
+
+    fn output_types(&self, _output: usize, _inputs: &[TypeSet]) -> TypeSet {
+        TypeSet::of::<Many<u32>>()
+    }
+
+    fn outputs(&self) -> &'static [OutputInfo] {
+        &[
+            OutputInfo {
+                name: "x",
+                ty_name: "u32",
+            },
+            OutputInfo {
+                name: "y",
+                ty_name: "u32",
+            },
+        ]
+    }
+}
+
+#[typetag::serde]
+impl crate::Node for GridNode {
+    fn name(&self) -> &'static str {
+        "grid"
+    }
+}
@@ -0,0 +1,128 @@
+use crate::{
+    AnyClone, FromAnyProto, InputComponent, InputError, InputStack, One, OutputInfo, Outputs,
+    PossibleInputs, TypeSet,
+};
+use once_cell::sync::Lazy;
+
+#[derive(Clone, FromAnyProto, InputComponent)]
+enum Input {
+    Frames(One<u32>),
+    Seconds(One<f32>),
+}
+
+impl Input {
+    fn op(self, ctx: &crate::EvalContext) -> Result<Box<dyn AnyClone>, InputError> {
+        // frames are counted at the context's frame rate rather than as they're drawn
+        let length = match self {
+            Input::Frames(frames) => frames.inner() as f64 / ctx.frame_rate as f64,
+            Input::Seconds(seconds) => seconds.inner() as f64,
+        };
+        if length.is_nan() || length <= 0. {
+            return Err(InputError::custom("the loop length must be more than zero"));
+        }
+        let loops = ctx.time as f64 / length;
+        Ok(Box::new(Outputs(vec![
+            Box::new(One::new(loops.fract() as f32)),
+            Box::new(One::new(ctx.time)),
+            Box::new(One::new(loops.trunc() as u32)),
+        ])))
+    }
+}
+
+// How far through a loop of some length the evaluation is. A whole number length is in frames
+// and any other is in seconds.
+#[derive(Copy, Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct LoopTimeNode;
+
+impl crate::NodeInput for LoopTimeNode {
+    fn inputs(&self) -> PossibleInputs<'static> {
+        static CACHE: Lazy<PossibleInputs> = Lazy::new(|| Input::possible_inputs(&["length"]));
+        PossibleInputs::new(&*CACHE.groups)
+    }
+}
+
+impl crate::NodeOutput for LoopTimeNode {
+    fn op(
+        &self,
+        inputs: &mut Vec<Box<dyn AnyClone>>,
+        ctx: &crate::EvalContext,
+    ) -> Result<Box<dyn AnyClone>, InputError> {
+        let input: Input = FromAnyProto::from_any(InputStack::new(inputs, ..))?;
+        input.op(ctx)
+    }
+
+    fn outputs(&self) -> &'static [OutputInfo] {
+        &[
+            OutputInfo {
+                name: "phase",
+                ty_name: "f32",
+            },
+            OutputInfo {
+                name: "seconds",
+                ty_name: "f32",
+            },
+            OutputInfo {
+                name: "loop",
+                ty_name: "u32",
+            },
+        ]
+    }
+
+    fn output_types(&self, output: usize, _inputs: &[TypeSet]) -> TypeSet {
+        if output == 2 {
+            TypeSet::of::<One<u32>>()
+        } else {
+            TypeSet::of::<One<f32>>()
+        }
+    }
+
+    fn is_time_varying(&self) -> bool {
+        true
+    }
+}
+
+#[typetag::serde]
+impl crate::Node for LoopTimeNode {
+    fn name(&self) -> &'static str {
+        "loop time"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::EvalContext;
+
+    fn outputs(input: Input, ctx: EvalContext) -> (f32, f32, u32) {
+        let outputs = input.op(&ctx).unwrap();
+        let Outputs(outputs) = *outputs.downcast::<Outputs>().unwrap();
+        let mut outputs = outputs.into_iter();
+        let mut next = || outputs.next().unwrap();
+        (
+            next().downcast::<One<f32>>().unwrap().inner(),
+            next().downcast::<One<f32>>().unwrap().inner(),
+            next().downcast::<One<u32>>().unwrap().inner(),
+        )
+    }
+
+    #[test]
+    fn loop_time() {
+        let ctx = EvalContext::fixed_step(540, 60.);
+        assert_eq!((0.25, 9., 2), outputs(Input::Seconds(One::new(4.)), ctx));
+        // 240 frames at 60 fps is the same 4 second loop
+        assert_eq!((0.25, 9., 2), outputs(Input::Frames(One::new(240)), ctx));
+        let ctx = EvalContext::fixed_step(6, 2.);
+        assert_eq!((0.5, 3., 0), outputs(Input::Frames(One::new(12)), ctx));
+        assert_eq!((0.75, 3., 0), outputs(Input::Seconds(One::new(4.)), ctx));
+        // however many frames have actually been drawn
+        let ctx = EvalContext {
+            frame: 1000,
+            ..EvalContext::default().at(540)
+        };
+        assert_eq!((0.25, 9., 2), outputs(Input::Frames(One::new(240)), ctx));
+
+        assert!(Input::Frames(One::new(0)).op(&ctx).is_err());
+        assert!(Input::Seconds(One::new(-1.)).op(&ctx).is_err());
+        assert!(Input::Seconds(One::new(f32::NAN)).op(&ctx).is_err());
+    }
+}
@@ -0,0 +1,508 @@
+use super::random::mix;
+use crate::{
+    one_many::op3, AnyClone, EvalContext, FromAnyProto, InputComponent, InputError, InputStack,
+    One, OneOrMany, PossibleInputs, TypeSet,
+};
+use once_cell::sync::Lazy;
+
+// Past this each octave is finer than an f32 coordinate can tell apart.
+const MAX_OCTAVES: u32 = 16;
+
+// A pseudo-random value for a corner of the lattice that gradient noise is built on.
+fn lattice(seed: u64, x: i32, y: i32, z: i32) -> u32 {
+    let xy = (x as u32 as u64) << 32 | y as u32 as u64;
+    let hash = mix(xy ^ seed.wrapping_mul(0x9e37_79b9_7f4a_7c15));
+    (mix(hash ^ z as u32 as u64) >> 32) as u32
+}
+
+// The dot product of the offset from a corner with one of the twelve gradients along the edges
+// of a cube, as in Ken Perlin's improved noise.
+fn grad(hash: u32, x: f32, y: f32, z: f32) -> f32 {
+    let h = hash & 15;
+    let u = if h < 8 { x } else { y };
+    let v = if h < 4 {
+        y
+    } else if h == 12 || h == 14 {
+        x
+    } else {
+        z
+    };
+    (if h & 1 == 0 { u } else { -u }) + (if h & 2 == 0 { v } else { -v })
+}
+
+fn fade(t: f32) -> f32 {
+    t * t * t * (t * (t * 6. - 15.) + 10.)
+}
+
+fn lerp(t: f32, a: f32, b: f32) -> f32 {
+    a + t * (b - a)
+}
+
+// Blends a value for each corner of the lattice cell around a point, where `corner` is given the
+// corner and the point's offset from it.
+fn blend<F>(x: f32, y: f32, z: f32, corner: F) -> f32
+where
+    F: Fn([i32; 3], [f32; 3]) -> f32,
+{
+    let (xf, yf, zf) = (x.floor(), y.floor(), z.floor());
+    let (x, y, z) = (x - xf, y - yf, z - zf);
+    let (xi, yi, zi) = (xf as i32, yf as i32, zf as i32);
+    let corner = |dx: i32, dy: i32, dz: i32| {
+        let lattice = [
+            xi.wrapping_add(dx),
+            yi.wrapping_add(dy),
+            zi.wrapping_add(dz),
+        ];
+        corner(lattice, [x - dx as f32, y - dy as f32, z - dz as f32])
+    };
+    let (u, v, w) = (fade(x), fade(y), fade(z));
+    let face = |dz| {
+        lerp(
+            v,
+            lerp(u, corner(0, 0, dz), corner(1, 0, dz)),
+            lerp(u, corner(0, 1, dz), corner(1, 1, dz)),
+        )
+    };
+    lerp(w, face(0), face(1))
+}
+
+// Perlin noise, roughly from -1 to 1 and zero on every whole coordinate. Leaving z at zero gives
+// 2D noise.
+fn gradient(seed: u64, x: f32, y: f32, z: f32) -> f32 {
+    blend(x, y, z, |[xi, yi, zi], [x, y, z]| {
+        grad(lattice(seed, xi, yi, zi), x, y, z)
+    })
+}
+
+// Perlin noise along a line. Six of the sixteen gradients `grad` picks from have no x component,
+// so a line through `gradient` would be flat in over a third of its cells.
+fn gradient_1d(seed: u64, x: f32) -> f32 {
+    let xf = x.floor();
+    let x = x - xf;
+    let corner = |dx: i32| {
+        let hash = lattice(seed, (xf as i32).wrapping_add(dx), 0, 0);
+        // any slope from -1 to 1
+        (hash as f32 / u32::MAX as f32 * 2. - 1.) * (x - dx as f32)
+    };
+    lerp(fade(x), corner(0), corner(1))
+}
+
+// A random value from -1 to 1 on every whole coordinate, smoothly blended in between. Blockier
+// than gradient noise since nearby corners can land on similar values.
+fn value(seed: u64, x: f32, y: f32, z: f32) -> f32 {
+    blend(x, y, z, |[xi, yi, zi], _| {
+        lattice(seed, xi, yi, zi) as f32 / u32::MAX as f32 * 2. - 1.
+    })
+}
+
+// Ken Perlin's simplex noise, from about -1 to 1. It sums gradients from the four corners of a
+// tetrahedron rather than the eight of a cube, which leaves fewer grid lines showing.
+fn simplex(seed: u64, x: f32, y: f32, z: f32) -> f32 {
+    const SKEW: f32 = 1. / 3.;
+    const UNSKEW: f32 = 1. / 6.;
+    let s = (x + y + z) * SKEW;
+    let (i, j, k) = ((x + s).floor(), (y + s).floor(), (z + s).floor());
+    let t = (i + j + k) * UNSKEW;
+    let (x, y, z) = (x - (i - t), y - (j - t), z - (k - t));
+    // the second and third corners of whichever of the cell's six tetrahedra the point is in
+    let (second, third) = if x >= y {
+        if y >= z {
+            ([1, 0, 0], [1, 1, 0])
+        } else if x >= z {
+            ([1, 0, 0], [1, 0, 1])
+        } else {
+            ([0, 0, 1], [1, 0, 1])
+        }
+    } else if y < z {
+        ([0, 0, 1], [0, 1, 1])
+    } else if x < z {
+        ([0, 1, 0], [0, 1, 1])
+    } else {
+        ([0, 1, 0], [1, 1, 0])
+    };
+    let (i, j, k) = (i as i32, j as i32, k as i32);
+    let corner = |[di, dj, dk]: [i32; 3]| {
+        let offset = (di + dj + dk) as f32 * UNSKEW;
+        let (x, y, z) = (
+            x - di as f32 + offset,
+            y - dj as f32 + offset,
+            z - dk as f32 + offset,
+        );
+        let t = 0.6 - x * x - y * y - z * z;
+        if t <= 0. {
+            return 0.;
+        }
+        let hash = lattice(
+            seed,
+            i.wrapping_add(di),
+            j.wrapping_add(dj),
+            k.wrapping_add(dk),
+        );
+        t * t * t * t * grad(hash, x, y, z)
+    };
+    32. * (corner([0, 0, 0]) + corner(second) + corner(third) + corner([1, 1, 1]))
+}
+
+#[derive(Copy, Clone)]
+enum Kind {
+    Gradient,
+    Simplex,
+    Value,
+}
+
+// Noise in as many dimensions as there are coordinates, where `line` means only x was given.
+fn noise(kind: Kind, seed: u64, line: bool, x: f32, y: f32, z: f32) -> f32 {
+    match kind {
+        Kind::Gradient if line => gradient_1d(seed, x),
+        Kind::Gradient => gradient(seed, x, y, z),
+        Kind::Simplex => simplex(seed, x, y, z),
+        Kind::Value => value(seed, x, y, z),
+    }
+}
+
+// Octaves of gradient noise, each twice the frequency of the last and `persistence` times its
+// strength, scaled back to the range of a single octave.
+fn fractal(seed: u64, line: bool, x: f32, y: f32, z: f32, octaves: u32, persistence: f32) -> f32 {
+    let (mut sum, mut total, mut amplitude, mut scale) = (0., 0., 1., 1.);
+    for octave in 0..octaves {
+        // each octave is seeded differently so they don't all line up at the origin
+        let seed = seed.wrapping_add(octave as u64);
+        sum += amplitude * noise(Kind::Gradient, seed, line, x * scale, y * scale, z * scale);
+        total += amplitude;
+        amplitude *= persistence;
+        scale *= 2.;
+    }
+    sum / total
+}
+
+#[derive(FromAnyProto, InputComponent)]
+struct NoiseInput {
+    x: OneOrMany<f32>,
+    y: Option<OneOrMany<f32>>,
+    z: Option<OneOrMany<f32>>,
+    seed: Option<One<u32>>,
+    frequency: Option<One<f32>>,
+}
+
+impl NoiseInput {
+    fn op(self, ctx: &EvalContext, kind: Kind) -> Box<dyn AnyClone> {
+        let seed = self.seed.map_or(0, One::inner);
+        let frequency = self.frequency.map_or(1., One::inner);
+        let seed = mix(ctx.seed) ^ seed as u64;
+        let line = self.y.is_none() && self.z.is_none();
+        sample(self.x, self.y, self.z, move |x, y, z| {
+            noise(
+                kind,
+                seed,
+                line,
+                x * frequency,
+                y * frequency,
+                z * frequency,
+            )
+        })
+    }
+}
+
+#[derive(FromAnyProto, InputComponent)]
+struct FractalInput {
+    x: OneOrMany<f32>,
+    y: Option<OneOrMany<f32>>,
+    z: Option<OneOrMany<f32>>,
+    seed: Option<One<u32>>,
+    frequency: Option<One<f32>>,
+    octaves: Option<One<u32>>,
+    persistence: Option<One<f32>>,
+}
+
+impl FractalInput {
+    fn op(self, ctx: &EvalContext) -> Result<Box<dyn AnyClone>, InputError> {
+        let seed = self.seed.map_or(0, One::inner);
+        let frequency = self.frequency.map_or(1., One::inner);
+        let octaves = self.octaves.map_or(4, One::inner);
+        let persistence = self.persistence.map_or(0.5, One::inner);
+        if !(1..=MAX_OCTAVES).contains(&octaves) {
+            return Err(InputError::custom(format!(
+                "octaves must be from 1 to {}",
+                MAX_OCTAVES
+            )));
+        }
+        if persistence.is_nan() || persistence < 0. {
+            return Err(InputError::custom("persistence can't be negative"));
+        }
+        let seed = mix(ctx.seed) ^ seed as u64;
+        let line = self.y.is_none() && self.z.is_none();
+        Ok(sample(self.x, self.y, self.z, move |x, y, z| {
+            let (x, y, z) = (x * frequency, y * frequency, z * frequency);
+            fractal(seed, line, x, y, z, octaves, persistence)
+        }))
+    }
+}
+
+// Noise at every point, with missing coordinates left at zero.
+fn sample<F>(
+    x: OneOrMany<f32>,
+    y: Option<OneOrMany<f32>>,
+    z: Option<OneOrMany<f32>>,
+    noise: F,
+) -> Box<dyn AnyClone>
+where
+    F: Fn(f32, f32, f32) -> f32 + Clone + Send + 'static,
+{
+    let y = y.unwrap_or(OneOrMany::One(One::new(0.)));
+    let z = z.unwrap_or(OneOrMany::One(One::new(0.)));
+    op3(x, y, z, noise).into_boxed_inner()
+}
+
+// Smoothly varying noise from roughly -1 to 1 at each point.
+#[derive(Copy, Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct GradientNoiseNode;
+
+impl crate::NodeInput for GradientNoiseNode {
+    fn inputs(&self) -> PossibleInputs<'static> {
+        static CACHE: Lazy<PossibleInputs> =
+            Lazy::new(|| NoiseInput::possible_inputs(&["x", "y", "z", "seed", "frequency"]));
+        PossibleInputs::new(&*CACHE.groups)
+    }
+}
+
+impl crate::NodeOutput for GradientNoiseNode {
+    fn op(
+        &self,
+        inputs: &mut Vec<Box<dyn AnyClone>>,
+        ctx: &EvalContext,
+    ) -> Result<Box<dyn AnyClone>, InputError> {
+        let input: NoiseInput = FromAnyProto::from_any(InputStack::new(inputs, ..))?;
+        Ok(input.op(ctx, Kind::Gradient))
+    }
+
+    fn output_types(&self, _output: usize, _inputs: &[TypeSet]) -> TypeSet {
+        TypeSet::one_or_many::<f32>()
+    }
+}
+
+#[typetag::serde]
+impl crate::Node for GradientNoiseNode {
+    fn name(&self) -> &'static str {
+        "gradient noise"
+    }
+}
+
+// Like gradient noise but with fewer grid lines showing.
+#[derive(Copy, Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct SimplexNoiseNode;
+
+impl crate::NodeInput for SimplexNoiseNode {
+    fn inputs(&self) -> PossibleInputs<'static> {
+        static CACHE: Lazy<PossibleInputs> =
+            Lazy::new(|| NoiseInput::possible_inputs(&["x", "y", "z", "seed", "frequency"]));
+        PossibleInputs::new(&*CACHE.groups)
+    }
+}
+
+impl crate::NodeOutput for SimplexNoiseNode {
+    fn op(
+        &self,
+        inputs: &mut Vec<Box<dyn AnyClone>>,
+        ctx: &EvalContext,
+    ) -> Result<Box<dyn AnyClone>, InputError> {
+        let input: NoiseInput = FromAnyProto::from_any(InputStack::new(inputs, ..))?;
+        Ok(input.op(ctx, Kind::Simplex))
+    }
+
+    fn output_types(&self, _output: usize, _inputs: &[TypeSet]) -> TypeSet {
+        TypeSet::one_or_many::<f32>()
+    }
+}
+
+#[typetag::serde]
+impl crate::Node for SimplexNoiseNode {
+    fn name(&self) -> &'static str {
+        "simplex noise"
+    }
+}
+
+// Random values at whole coordinates blended smoothly in between.
+#[derive(Copy, Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct ValueNoiseNode;
+
+impl crate::NodeInput for ValueNoiseNode {
+    fn inputs(&self) -> PossibleInputs<'static> {
+        static CACHE: Lazy<PossibleInputs> =
+            Lazy::new(|| NoiseInput::possible_inputs(&["x", "y", "z", "seed", "frequency"]));
+        PossibleInputs::new(&*CACHE.groups)
+    }
+}
+
+impl crate::NodeOutput for ValueNoiseNode {
+    fn op(
+        &self,
+        inputs: &mut Vec<Box<dyn AnyClone>>,
+        ctx: &EvalContext,
+    ) -> Result<Box<dyn AnyClone>, InputError> {
+        let input: NoiseInput = FromAnyProto::from_any(InputStack::new(inputs, ..))?;
+        Ok(input.op(ctx, Kind::Value))
+    }
+
+    fn output_types(&self, _output: usize, _inputs: &[TypeSet]) -> TypeSet {
+        TypeSet::one_or_many::<f32>()
+    }
+}
+
+#[typetag::serde]
+impl crate::Node for ValueNoiseNode {
+    fn name(&self) -> &'static str {
+        "value noise"
+    }
+}
+
+// Gradient noise with finer detail layered on top.
+#[derive(Copy, Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct FractalNoiseNode;
+
+impl crate::NodeInput for FractalNoiseNode {
+    fn inputs(&self) -> PossibleInputs<'static> {
+        static CACHE: Lazy<PossibleInputs> = Lazy::new(|| {
+            FractalInput::possible_inputs(&[
+                "x",
+                "y",
+                "z",
+                "seed",
+                "frequency",
+                "octaves",
+                "persistence",
+            ])
+        });
+        PossibleInputs::new(&*CACHE.groups)
+    }
+}
+
+impl crate::NodeOutput for FractalNoiseNode {
+    fn op(
+        &self,
+        inputs: &mut Vec<Box<dyn AnyClone>>,
+        ctx: &EvalContext,
+    ) -> Result<Box<dyn AnyClone>, InputError> {
+        let input: FractalInput = FromAnyProto::from_any(InputStack::new(inputs, ..))?;
+        input.op(ctx)
+    }
+
+    fn output_types(&self, _output: usize, _inputs: &[TypeSet]) -> TypeSet {
+        TypeSet::one_or_many::<f32>()
+    }
+}
+
+#[typetag::serde]
+impl crate::Node for FractalNoiseNode {
+    fn name(&self) -> &'static str {
+        "fractal noise"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Many;
+
+    #[test]
+    fn gradient_noise() {
+        let points = (0..40)
+            .flat_map(|x| (0..40).map(move |y| (x as f32 * 0.13, y as f32 * 0.17)))
+            .collect::<Vec<_>>();
+        let values = points
+            .iter()
+            .map(|&(x, y)| gradient(0, x, y, 0.7))
+            .collect::<Vec<_>>();
+        assert!(values.iter().all(|v| v.abs() <= 1.));
+        assert!(values.iter().any(|v| *v < -0.4) && values.iter().any(|v| *v > 0.4));
+        // smooth, zero on whole coordinates and the same on every machine
+        for &(x, y) in &points {
+            assert!((gradient(0, x, y, 0.7) - gradient(0, x + 1e-3, y, 0.7)).abs() < 1e-2);
+        }
+        assert_eq!(0., gradient(0, 3., -2., 0.));
+        assert_eq!(0.04907894, gradient(0, 0.5, 1.25, 2.75));
+        assert_ne!(gradient(0, 0.5, 0.5, 0.), gradient(1, 0.5, 0.5, 0.));
+
+        // a line slopes away from zero in every cell
+        let values = (-500..500)
+            .map(|x| gradient_1d(3, x as f32 + 0.25))
+            .collect::<Vec<_>>();
+        assert!(values.iter().all(|v| *v != 0. && v.abs() <= 1.));
+        assert_eq!(0., gradient_1d(3, 4.));
+        assert!((gradient_1d(3, 4.5) - gradient_1d(3, 4.501)).abs() < 1e-2);
+
+        let input = NoiseInput {
+            x: OneOrMany::Many(Many::from(vec![0.25, 0.5, 0.75])),
+            y: Some(OneOrMany::One(One::new(1.5))),
+            z: None,
+            seed: None,
+            frequency: Some(One::new(2.)),
+        };
+        let output = input.op(&EvalContext::default(), Kind::Gradient);
+        let output = output.downcast::<Many<f32>>().unwrap().collect::<Vec<_>>();
+        assert_eq!(
+            vec![gradient(0, 0.5, 3., 0.), 0., gradient(0, 1.5, 3., 0.)],
+            output
+        );
+    }
+
+    #[test]
+    fn simplex_and_value_noise() {
+        for &kind in &[Kind::Simplex, Kind::Value] {
+            let sample = |x: f32, y: f32, z: f32| noise(kind, 5, false, x, y, z);
+            let values = (0..2000)
+                .map(|i| sample(i as f32 * 0.37, i as f32 * 0.11, i as f32 * 0.05))
+                .collect::<Vec<_>>();
+            assert!(values.iter().all(|v| v.abs() <= 1.));
+            assert!(values.iter().any(|v| *v < -0.4) && values.iter().any(|v| *v > 0.4));
+            for i in 0..200 {
+                let x = i as f32 * 0.29;
+                assert!((sample(x, 0.4, 0.7) - sample(x + 1e-3, 0.4, 0.7)).abs() < 1e-2);
+            }
+            // a line isn't flat anywhere
+            let line = (0..500).map(|i| sample(i as f32 * 0.5 + 0.25, 0., 0.));
+            assert!(line.collect::<Vec<_>>().windows(2).all(|w| w[0] != w[1]));
+            assert_ne!(sample(0.5, 0.5, 0.), noise(kind, 6, false, 0.5, 0.5, 0.));
+        }
+        // value noise goes through the lattice's value at every whole coordinate
+        let corner = lattice(5, 3, -2, 0) as f32 / u32::MAX as f32 * 2. - 1.;
+        assert_eq!(corner, value(5, 3., -2., 0.));
+    }
+
+    #[test]
+    fn fractal_noise() {
+        assert_eq!(
+            gradient(7, 0.3, 0.6, 0.9),
+            fractal(7, false, 0.3, 0.6, 0.9, 1, 0.5)
+        );
+        let values = (0..1000)
+            .map(|i| fractal(7, false, i as f32 * 0.37, 0.2, 0., 6, 0.7))
+            .collect::<Vec<_>>();
+        assert!(values.iter().all(|v| v.abs() <= 1.));
+
+        let input = |octaves, persistence| FractalInput {
+            x: OneOrMany::One(One::new(0.5)),
+            y: None,
+            z: None,
+            seed: Some(One::new(7)),
+            frequency: None,
+            octaves: Some(One::new(octaves)),
+            persistence: Some(One::new(persistence)),
+        };
+        let ctx = EvalContext::default();
+        let output = input(3, 0.5).op(&ctx).unwrap();
+        assert_eq!(
+            fractal(mix(0) ^ 7, true, 0.5, 0., 0., 3, 0.5),
+            output.downcast::<One<f32>>().unwrap().inner()
+        );
+        assert!(input(0, 0.5).op(&ctx).is_err());
+        assert!(input(17, 0.5).op(&ctx).is_err());
+        assert!(input(3, -0.5).op(&ctx).is_err());
+        // the context's seed changes everything
+        let reseeded = input(3, 0.5).op(&ctx.with_seed(1)).unwrap();
+        let output = input(3, 0.5).op(&ctx).unwrap();
+        assert_ne!(
+            output.downcast::<One<f32>>().unwrap().inner(),
+            reseeded.downcast::<One<f32>>().unwrap().inner()
+        );
+    }
+}
@@ -0,0 +1,257 @@
+use crate::{
+    one_many::op1, AnyClone, EvalContext, FromAnyProto, InputComponent, InputError, InputStack,
+    One, OneOrMany, OutputInfo, Outputs, PossibleInputs, TypeSet,
+};
+use once_cell::sync::Lazy;
+
+// A stateless hash of everything that picks a value so that the same index gives the same value
+// on every frame and every machine. The context's seed lets a whole graph be reseeded at once.
+fn hash(ctx: &EvalContext, seed: u32, index: u32) -> u32 {
+    let x = ((seed as u64) << 32 | index as u64)
+        .wrapping_add(ctx.seed.wrapping_add(1).wrapping_mul(0x9e37_79b9_7f4a_7c15));
+    (mix(x) >> 32) as u32
+}
+
+// The splitmix64 finaliser, which spreads every bit of the input across the whole output.
+pub(super) fn mix(mut x: u64) -> u64 {
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    x ^ (x >> 31)
+}
+
+// From 0 up to but not including 1.
+fn unit(hash: u32) -> f32 {
+    (hash >> 8) as f32 / (1 << 24) as f32
+}
+
+#[derive(FromAnyProto, InputComponent)]
+struct FloatInput {
+    seed: One<u32>,
+    index: OneOrMany<u32>,
+    min: One<f32>,
+    max: One<f32>,
+}
+
+impl FloatInput {
+    fn op(self, ctx: EvalContext) -> Box<dyn AnyClone> {
+        let (seed, min, max) = (self.seed.inner(), self.min.inner(), self.max.inner());
+        op1(self.index, move |index| {
+            min + unit(hash(&ctx, seed, index)) * (max - min)
+        })
+        .into_boxed_inner()
+    }
+}
+
+#[derive(FromAnyProto, InputComponent)]
+struct IntegerInput {
+    seed: One<u32>,
+    index: OneOrMany<u32>,
+    min: One<u32>,
+    max: One<u32>,
+}
+
+impl IntegerInput {
+    fn op(self, ctx: EvalContext) -> Result<Box<dyn AnyClone>, InputError> {
+        let (seed, min, max) = (self.seed.inner(), self.min.inner(), self.max.inner());
+        if max <= min {
+            return Err(InputError::custom("max must be more than min"));
+        }
+        let range = (max - min) as u64;
+        Ok(op1(self.index, move |index| {
+            min + ((hash(&ctx, seed, index) as u64 * range) >> 32) as u32
+        })
+        .into_boxed_inner())
+    }
+}
+
+#[derive(FromAnyProto, InputComponent)]
+struct DirectionInput {
+    seed: One<u32>,
+    index: OneOrMany<u32>,
+}
+
+impl DirectionInput {
+    fn op(self, ctx: EvalContext) -> Box<dyn AnyClone> {
+        let seed = self.seed.inner();
+        let angle = move |index| unit(hash(&ctx, seed, index)) * std::f32::consts::PI * 2.;
+        let x = op1(self.index.clone(), move |index| angle(index).cos());
+        let y = op1(self.index, move |index| angle(index).sin());
+        Box::new(Outputs(vec![x.into_boxed_inner(), y.into_boxed_inner()]))
+    }
+}
+
+// A number from `min` up to but not including `max` for each index.
+#[derive(Copy, Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct RandomFloatNode;
+
+impl crate::NodeInput for RandomFloatNode {
+    fn inputs(&self) -> PossibleInputs<'static> {
+        static CACHE: Lazy<PossibleInputs> =
+            Lazy::new(|| FloatInput::possible_inputs(&["seed", "index", "min", "max"]));
+        PossibleInputs::new(&*CACHE.groups)
+    }
+}
+
+impl crate::NodeOutput for RandomFloatNode {
+    fn op(
+        &self,
+        inputs: &mut Vec<Box<dyn AnyClone>>,
+        ctx: &EvalContext,
+    ) -> Result<Box<dyn AnyClone>, InputError> {
+        let input: FloatInput = FromAnyProto::from_any(InputStack::new(inputs, ..))?;
+        Ok(input.op(*ctx))
+    }
+
+    fn output_types(&self, _output: usize, _inputs: &[TypeSet]) -> TypeSet {
+        TypeSet::one_or_many::<f32>()
+    }
+}
+
+#[typetag::serde]
+impl crate::Node for RandomFloatNode {
+    fn name(&self) -> &'static str {
+        "random float"
+    }
+}
+
+// A whole number from `min` up to but not including `max` for each index.
+#[derive(Copy, Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct RandomIntegerNode;
+
+impl crate::NodeInput for RandomIntegerNode {
+    fn inputs(&self) -> PossibleInputs<'static> {
+        static CACHE: Lazy<PossibleInputs> =
+            Lazy::new(|| IntegerInput::possible_inputs(&["seed", "index", "min", "max"]));
+        PossibleInputs::new(&*CACHE.groups)
+    }
+}
+
+impl crate::NodeOutput for RandomIntegerNode {
+    fn op(
+        &self,
+        inputs: &mut Vec<Box<dyn AnyClone>>,
+        ctx: &EvalContext,
+    ) -> Result<Box<dyn AnyClone>, InputError> {
+        let input: IntegerInput = FromAnyProto::from_any(InputStack::new(inputs, ..))?;
+        input.op(*ctx)
+    }
+
+    fn output_types(&self, _output: usize, _inputs: &[TypeSet]) -> TypeSet {
+        TypeSet::one_or_many::<u32>()
+    }
+}
+
+#[typetag::serde]
+impl crate::Node for RandomIntegerNode {
+    fn name(&self) -> &'static str {
+        "random integer"
+    }
+}
+
+// A unit vector pointing in a random direction for each index.
+#[derive(Copy, Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct RandomDirectionNode;
+
+impl crate::NodeInput for RandomDirectionNode {
+    fn inputs(&self) -> PossibleInputs<'static> {
+        static CACHE: Lazy<PossibleInputs> =
+            Lazy::new(|| DirectionInput::possible_inputs(&["seed", "index"]));
+        PossibleInputs::new(&*CACHE.groups)
+    }
+}
+
+impl crate::NodeOutput for RandomDirectionNode {
+    fn op(
+        &self,
+        inputs: &mut Vec<Box<dyn AnyClone>>,
+        ctx: &EvalContext,
+    ) -> Result<Box<dyn AnyClone>, InputError> {
+        let input: DirectionInput = FromAnyProto::from_any(InputStack::new(inputs, ..))?;
+        Ok(input.op(*ctx))
+    }
+
+    fn output_types(&self, _output: usize, _inputs: &[TypeSet]) -> TypeSet {
+        TypeSet::one_or_many::<f32>()
+    }
+
+    fn outputs(&self) -> &'static [OutputInfo] {
+        &[
+            OutputInfo {
+                name: "x",
+                ty_name: "f32",
+            },
+            OutputInfo {
+                name: "y",
+                ty_name: "f32",
+            },
+        ]
+    }
+}
+
+#[typetag::serde]
+impl crate::Node for RandomDirectionNode {
+    fn name(&self) -> &'static str {
+        "random direction"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Many;
+
+    fn many<T: Clone + std::fmt::Debug + 'static>(output: Box<dyn AnyClone>) -> Vec<T> {
+        output.downcast::<Many<T>>().unwrap().collect()
+    }
+
+    #[test]
+    fn random() {
+        let ctx = EvalContext::default();
+        let indices = || OneOrMany::Many(Many::from(0..1000));
+
+        let float = |ctx: EvalContext, seed| {
+            let input = FloatInput {
+                seed: One::new(seed),
+                index: indices(),
+                min: One::new(-2.),
+                max: One::new(3.),
+            };
+            many::<f32>(input.op(ctx))
+        };
+        let floats = float(ctx, 1);
+        assert!(floats.iter().all(|v| (-2. ..3.).contains(v)));
+        assert!(floats.iter().any(|v| *v < -1.5) && floats.iter().any(|v| *v > 2.5));
+        // the same every time, on every machine, and different for another seed
+        assert_eq!(floats, float(ctx.at(100), 1));
+        assert_eq!(0xb370_3ad8, hash(&ctx, 1, 2));
+        assert_ne!(floats, float(ctx, 2));
+        assert_ne!(floats, float(ctx.with_seed(1), 1));
+
+        let integer = |min, max| IntegerInput {
+            seed: One::new(1),
+            index: indices(),
+            min: One::new(min),
+            max: One::new(max),
+        };
+        let integers = many::<u32>(integer(5, 8).op(ctx).unwrap());
+        for value in 5..8 {
+            assert!(integers.contains(&value));
+        }
+        assert!(integers.iter().all(|v| (5..8).contains(v)));
+        assert!(integer(5, 5).op(ctx).is_err());
+
+        let input = DirectionInput {
+            seed: One::new(1),
+            index: indices(),
+        };
+        let Outputs(outputs) = *input.op(ctx).downcast::<Outputs>().unwrap();
+        let mut outputs = outputs.into_iter();
+        let x = many::<f32>(outputs.next().unwrap());
+        let y = many::<f32>(outputs.next().unwrap());
+        assert_eq!(1000, x.len());
+        assert!(x
+            .iter()
+            .zip(&y)
+            .all(|(x, y)| (x * x + y * y - 1.).abs() < 1e-5));
+    }
+}
@@ -1,5 +1,8 @@
-use crate::{FromAnyProto, InputComponent, InputStack, Many, OneOrMany, PossibleInputs};
-use std::any::Any;
+use crate::codegen::{self, Expr, Scalar};
+use crate::{
+    AnyClone, FromAnyProto, InputComponent, InputError, InputStack, Many, OneOrMany,
+    PossibleInputs, TypeSet,
+};
 
 #[derive(FromAnyProto, InputComponent)]
 struct RangeNodeInput {
@@ -7,7 +10,7 @@ struct RangeNodeInput {
 }
 
 impl RangeNodeInput {
-    fn op(self) -> Box<dyn Any> {
+    fn op(self) -> Box<dyn AnyClone> {
         let iter = match self.length {
             OneOrMany::One(length) => Many::from(0..length.inner()),
             OneOrMany::Many(length) => {
@@ -32,9 +35,32 @@ impl crate::NodeInput for RangeNode {
 }
 
 impl crate::NodeOutput for RangeNode {
-    fn op(&self, inputs: &mut Vec<Box<dyn Any>>) -> Result<Box<dyn Any>, ()> {
+    fn op(
+        &self,
+        inputs: &mut Vec<Box<dyn AnyClone>>,
+        _ctx: &crate::EvalContext,
+    ) -> Result<Box<dyn AnyClone>, InputError> {
         FromAnyProto::from_any(InputStack::new(inputs, ..)).map(RangeNodeInput::op)
     }
+
+    fn output_types(&self, _output: usize, _inputs: &[TypeSet]) -> TypeSet {
+        TypeSet::of::<Many<u32>>()
+    }
+
+    fn codegen(&self, _output: usize, inputs: &[Option<Expr>]) -> Result<Expr, InputError> {
+        let length = codegen::input(inputs, 0)?;
+        match (length.scalar, length.many) {
+            (Scalar::U32, false) => Ok(Expr::many(Scalar::U32, format!("0..{}", length.code))),
+            (Scalar::U32, true) => Ok(Expr::many(
+                Scalar::U32,
+                format!(
+                    "{{ let length = {}; length.clone().flat_map(move |_| length.clone()) }}",
+                    length.code
+                ),
+            )),
+            _ => Err(codegen::mismatch(0, "u32", length)),
+        }
+    }
 }
 
 #[typetag::serde]
@@ -1,6 +1,6 @@
 use super::arithmetic::ArithmeticNodeInput;
-use crate::{InputStack, PossibleInputs};
-use std::any::Any;
+use crate::codegen::{Expr, Scalar};
+use crate::{AnyClone, InputError, InputStack, PossibleInputs, TypeSet};
 
 #[derive(Debug, Copy, Clone, Default, serde::Serialize, serde::Deserialize)]
 pub struct RatioNode;
@@ -16,9 +16,30 @@ impl crate::NodeInput for RatioNode {
 }
 
 impl crate::NodeOutput for RatioNode {
-    fn op(&self, inputs: &mut Vec<Box<dyn Any>>) -> Result<Box<dyn Any>, ()> {
+    fn op(
+        &self,
+        inputs: &mut Vec<Box<dyn AnyClone>>,
+        _ctx: &crate::EvalContext,
+    ) -> Result<Box<dyn AnyClone>, InputError> {
         crate::FromAnyProto::from_any(InputStack::new(inputs, ..)).map(ArithmeticNodeInput::ratio)
     }
+
+    fn output_types(&self, _output: usize, _inputs: &[TypeSet]) -> TypeSet {
+        TypeSet::one_or_many::<f32>()
+    }
+
+    fn codegen(&self, _output: usize, inputs: &[Option<Expr>]) -> Result<Expr, InputError> {
+        ArithmeticNodeInput::codegen(
+            inputs,
+            (Scalar::F32, |count, length| {
+                format!(
+                    "if {1} == 0 {{ 0. }} else {{ (({0} % {1}) as f64 / {1} as f64) as f32 }}",
+                    count, length
+                )
+            }),
+            |count, length| format!("({0} % {1}) / {1}", count, length),
+        )
+    }
 }
 
 #[typetag::serde]
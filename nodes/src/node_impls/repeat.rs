@@ -1,5 +1,8 @@
-use crate::{FromAnyProto, InputComponent, InputStack, Many, One, OneOrMany, PossibleInputs};
-use std::any::Any;
+use crate::codegen::{self, Expr, Scalar};
+use crate::{
+    AnyClone, FromAnyProto, InputComponent, InputError, InputStack, Many, One, OneOrMany,
+    PossibleInputs, TypeSet,
+};
 
 #[derive(FromAnyProto, InputComponent)]
 struct RepeatNodeInput {
@@ -8,7 +11,7 @@ struct RepeatNodeInput {
 }
 
 impl RepeatNodeInput {
-    fn op(self) -> Box<dyn Any> {
+    fn op(self) -> Box<dyn AnyClone> {
         let count = self.count.inner();
         let iter = match self.value {
             OneOrMany::One(v) => {
@@ -37,9 +40,34 @@ impl crate::NodeInput for RepeatNode {
 }
 
 impl crate::NodeOutput for RepeatNode {
-    fn op(&self, inputs: &mut Vec<Box<dyn Any>>) -> Result<Box<dyn Any>, ()> {
+    fn op(
+        &self,
+        inputs: &mut Vec<Box<dyn AnyClone>>,
+        _ctx: &crate::EvalContext,
+    ) -> Result<Box<dyn AnyClone>, InputError> {
         FromAnyProto::from_any(InputStack::new(inputs, ..)).map(RepeatNodeInput::op)
     }
+
+    fn output_types(&self, _output: usize, _inputs: &[TypeSet]) -> TypeSet {
+        TypeSet::of::<Many<u32>>()
+    }
+
+    fn codegen(&self, _output: usize, inputs: &[Option<Expr>]) -> Result<Expr, InputError> {
+        let count = codegen::input(inputs, 0)?;
+        if count.scalar != Scalar::U32 || count.many {
+            return Err(codegen::mismatch(0, "One<u32>", count));
+        }
+        let value = codegen::input(inputs, 1)?;
+        let code = match (value.scalar, value.many) {
+            (Scalar::U32, false) => format!("(0..{}).map(move |_| {})", count.code, value.code),
+            (Scalar::U32, true) => format!(
+                "{{ let value = {}; (0..{}).flat_map(move |r| value.clone().map(move |_| r)) }}",
+                value.code, count.code
+            ),
+            _ => return Err(codegen::mismatch(1, "u32", value)),
+        };
+        Ok(Expr::many(Scalar::U32, code))
+    }
 }
 
 #[typetag::serde]
@@ -1,14 +1,17 @@
-use crate::{FromAnyProto, InputComponent, InputStack, OneOrMany, PossibleInputs};
-use std::any::Any;
+use crate::codegen::{self, Expr, Scalar};
+use crate::{
+    AnyClone, FromAnyProto, InputComponent, InputError, InputStack, OneOrMany, OutputInfo, Outputs,
+    PossibleInputs, TypeSet,
+};
 
-#[derive(FromAnyProto, InputComponent)]
+#[derive(Clone, FromAnyProto, InputComponent)]
 enum Input {
     F32(OneOrMany<f32>),
     U32(OneOrMany<u32>),
 }
 
 impl Input {
-    fn sin(self) -> Box<dyn Any> {
+    fn sin(self) -> Box<dyn AnyClone> {
         use crate::one_many::op1;
         match self {
             Input::F32(inner) => op1(inner, f32::sin).into_boxed_inner(),
@@ -16,7 +19,7 @@ impl Input {
         }
     }
 
-    fn cos(self) -> Box<dyn Any> {
+    fn cos(self) -> Box<dyn AnyClone> {
         use crate::one_many::op1;
         match self {
             Input::F32(inner) => op1(inner, f32::cos).into_boxed_inner(),
@@ -24,6 +27,21 @@ impl Input {
         }
     }
 
+    fn sin_cos(self) -> Box<dyn AnyClone> {
+        Box::new(Outputs(vec![self.clone().sin(), self.cos()]))
+    }
+
+    // Code that applies the f32 method `function` to the input.
+    fn codegen(inputs: &[Option<Expr>], function: &str) -> Result<Expr, InputError> {
+        let number = codegen::input(inputs, 0)?;
+        if number.scalar == Scalar::String {
+            return Err(codegen::mismatch(0, "u32 or f32", number));
+        }
+        Ok(codegen::op1(number, Scalar::F32, |v| {
+            format!("{}.{}()", codegen::float(v, number.scalar), function)
+        }))
+    }
+
     fn inputs() -> PossibleInputs<'static> {
         use once_cell::sync::Lazy;
         static CACHE: Lazy<PossibleInputs> = Lazy::new(|| Input::possible_inputs(&["number"]));
@@ -41,9 +59,21 @@ impl crate::NodeInput for SineNode {
 }
 
 impl crate::NodeOutput for SineNode {
-    fn op(&self, inputs: &mut Vec<Box<dyn Any>>) -> Result<Box<dyn Any>, ()> {
+    fn op(
+        &self,
+        inputs: &mut Vec<Box<dyn AnyClone>>,
+        _ctx: &crate::EvalContext,
+    ) -> Result<Box<dyn AnyClone>, InputError> {
         FromAnyProto::from_any(InputStack::new(inputs, ..)).map(Input::sin)
     }
+
+    fn output_types(&self, _output: usize, _inputs: &[TypeSet]) -> TypeSet {
+        TypeSet::one_or_many::<f32>()
+    }
+
+    fn codegen(&self, _output: usize, inputs: &[Option<Expr>]) -> Result<Expr, InputError> {
+        Input::codegen(inputs, "sin")
+    }
 }
 
 #[typetag::serde]
@@ -63,9 +93,21 @@ impl crate::NodeInput for CosNode {
 }
 
 impl crate::NodeOutput for CosNode {
-    fn op(&self, inputs: &mut Vec<Box<dyn Any>>) -> Result<Box<dyn Any>, ()> {
+    fn op(
+        &self,
+        inputs: &mut Vec<Box<dyn AnyClone>>,
+        _ctx: &crate::EvalContext,
+    ) -> Result<Box<dyn AnyClone>, InputError> {
         FromAnyProto::from_any(InputStack::new(inputs, ..)).map(Input::cos)
     }
+
+    fn output_types(&self, _output: usize, _inputs: &[TypeSet]) -> TypeSet {
+        TypeSet::one_or_many::<f32>()
+    }
+
+    fn codegen(&self, _output: usize, inputs: &[Option<Expr>]) -> Result<Expr, InputError> {
+        Input::codegen(inputs, "cos")
+    }
 }
 
 #[typetag::serde]
@@ -74,3 +116,51 @@ impl crate::Node for CosNode {
         "cosine"
     }
 }
+
+#[derive(Copy, Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct SinCosNode;
+
+impl crate::NodeInput for SinCosNode {
+    fn inputs(&self) -> PossibleInputs<'static> {
+        Input::inputs()
+    }
+}
+
+impl crate::NodeOutput for SinCosNode {
+    fn op(
+        &self,
+        inputs: &mut Vec<Box<dyn AnyClone>>,
+        _ctx: &crate::EvalContext,
+    ) -> Result<Box<dyn AnyClone>, InputError> {
+        FromAnyProto::from_any(InputStack::new(inputs, ..)).map(Input::sin_cos)
+    }
+
+    fn output_types(&self, _output: usize, _inputs: &[TypeSet]) -> TypeSet {
+        TypeSet::one_or_many::<f32>()
+    }
+
+    fn codegen(&self, output: usize, inputs: &[Option<Expr>]) -> Result<Expr, InputError> {
+        let function = if output == 0 { "sin" } else { "cos" };
+        Input::codegen(inputs, function)
+    }
+
+    fn outputs(&self) -> &'static [OutputInfo] {
+        &[
+            OutputInfo {
+                name: "sin",
+                ty_name: "f32",
+            },
+            OutputInfo {
+                name: "cos",
+                ty_name: "f32",
+            },
+        ]
+    }
+}
+
+#[typetag::serde]
+impl crate::Node for SinCosNode {
+    fn name(&self) -> &'static str {
+        "sin cos"
+    }
+}
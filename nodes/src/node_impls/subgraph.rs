@@ -0,0 +1,157 @@
+use crate::{AnyClone, Graph, InputError, InputGroup, InputInfo, NodeID, PossibleInputs, TypeSet};
+use std::sync::{Mutex, MutexGuard};
+
+// An otherwise unconnected input of a node inside the subgraph that is fed from outside of it.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct ExposedInput {
+    pub node: NodeID,
+    pub input: usize,
+    pub name: String,
+}
+
+// The inner graph is behind a mutex so that it can keep its cached outputs between executions
+// while still being shared.
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub struct SubgraphNode {
+    graph: Mutex<Graph>,
+    inputs: Vec<ExposedInput>,
+}
+
+impl Clone for SubgraphNode {
+    fn clone(&self) -> Self {
+        Self::new(self.graph().clone(), self.inputs.clone())
+    }
+}
+
+impl SubgraphNode {
+    pub fn new(graph: Graph, inputs: Vec<ExposedInput>) -> Self {
+        Self {
+            graph: Mutex::new(graph),
+            inputs,
+        }
+    }
+
+    pub fn graph(&self) -> MutexGuard<'_, Graph> {
+        self.graph.lock().unwrap()
+    }
+
+    pub fn exposed_inputs(&self) -> &[ExposedInput] {
+        &self.inputs
+    }
+
+    pub fn into_inner(self) -> (Graph, Vec<ExposedInput>) {
+        (self.graph.into_inner().unwrap(), self.inputs)
+    }
+}
+
+impl crate::NodeInput for SubgraphNode {
+    // Exposed inputs usually belong to different nodes, so there's no telling which combinations
+    // of their types go together.
+    fn independent_inputs(&self) -> bool {
+        true
+    }
+
+    fn inputs(&self) -> PossibleInputs<'static> {
+        if self.inputs.is_empty() {
+            return PossibleInputs::new(vec![]);
+        }
+
+        let graph = self.graph();
+        let types = self
+            .inputs
+            .iter()
+            .map(|exposed| {
+                let mut infos: Vec<InputInfo<'static>> = vec![];
+                if let Some(node) = graph.nodes().get(exposed.node) {
+                    let possible = node.inputs();
+                    for group in possible.groups.iter() {
+                        let info = if node.variadic() {
+                            group.info.first()
+                        } else {
+                            group.info.get(exposed.input)
+                        };
+                        if let Some(info) = info {
+                            if infos.iter().all(|i| i.type_id != info.type_id) {
+                                infos.push(InputInfo {
+                                    name: exposed.name.clone().into(),
+                                    ty_name: info.ty_name,
+                                    type_id: info.type_id,
+                                    optional: info.optional,
+                                });
+                            }
+                        }
+                    }
+                }
+                infos
+            })
+            .collect::<Vec<_>>();
+        if types.iter().any(Vec::is_empty) {
+            return PossibleInputs::new(vec![]);
+        }
+        // one group per position in the longest list, with shorter lists repeating their last type
+        let count = types.iter().map(Vec::len).max().unwrap_or(0);
+        let groups = (0..count)
+            .map(|index| InputGroup {
+                info: types
+                    .iter()
+                    .map(|infos| infos[index.min(infos.len() - 1)].clone())
+                    .collect(),
+            })
+            .collect::<Vec<_>>();
+        PossibleInputs::new(groups)
+    }
+}
+
+impl crate::NodeOutput for SubgraphNode {
+    fn op(
+        &self,
+        inputs: &mut Vec<Box<dyn AnyClone>>,
+        ctx: &crate::EvalContext,
+    ) -> Result<Box<dyn AnyClone>, InputError> {
+        let inputs = self
+            .inputs
+            .iter()
+            .zip(inputs.drain(..))
+            .map(|(exposed, value)| (exposed.node, exposed.input, value))
+            .collect::<Vec<_>>();
+        let mut graph = self.graph();
+        graph.set_context(*ctx);
+        graph.execute_with(&inputs).map_err(InputError::custom)
+    }
+
+    // the root's outputs are handed on as they are
+    fn outputs(&self) -> &'static [crate::OutputInfo] {
+        let graph = self.graph();
+        graph.nodes()[graph.root()].outputs()
+    }
+
+    fn output_types(&self, _output: usize, inputs: &[TypeSet]) -> TypeSet {
+        let external = self
+            .inputs
+            .iter()
+            .zip(inputs.iter())
+            .map(|(exposed, types)| (exposed.node, exposed.input, types.clone()))
+            .collect::<Vec<_>>();
+        let (_, mut outputs) = self.graph().infer_types_with(&external);
+        // a root with several outputs hands all of them on at once
+        if outputs.len() == 1 {
+            outputs.remove(0)
+        } else {
+            TypeSet::Any
+        }
+    }
+
+    fn is_time_varying(&self) -> bool {
+        self.graph()
+            .nodes()
+            .values()
+            .any(|node| node.is_time_varying())
+    }
+}
+
+#[typetag::serde]
+impl crate::Node for SubgraphNode {
+    fn name(&self) -> &'static str {
+        "subgraph"
+    }
+}
@@ -1,5 +1,8 @@
-use crate::{FromAnyProto, InputComponent, InputStack, OneOrMany, PossibleInputs};
-use std::any::Any;
+use crate::codegen::{self, Expr, Scalar};
+use crate::{
+    AnyClone, FromAnyProto, InputComponent, InputError, InputStack, OneOrMany, PossibleInputs,
+    TypeSet,
+};
 
 #[derive(FromAnyProto, InputComponent)]
 enum Input {
@@ -8,7 +11,7 @@ enum Input {
 }
 
 impl Input {
-    fn op(self) -> Box<dyn Any> {
+    fn op(self) -> Box<dyn AnyClone> {
         use crate::one_many::op1;
         match self {
             Input::U32(v) => op1(v, |v| v as f32),
@@ -30,9 +33,28 @@ impl crate::NodeInput for ToFloatNode {
 }
 
 impl crate::NodeOutput for ToFloatNode {
-    fn op(&self, inputs: &mut Vec<Box<dyn Any>>) -> Result<Box<dyn Any>, ()> {
+    fn op(
+        &self,
+        inputs: &mut Vec<Box<dyn AnyClone>>,
+        _ctx: &crate::EvalContext,
+    ) -> Result<Box<dyn AnyClone>, InputError> {
         Input::from_any(InputStack::new(inputs, ..)).map(Input::op)
     }
+
+    fn output_types(&self, _output: usize, _inputs: &[TypeSet]) -> TypeSet {
+        TypeSet::one_or_many::<f32>()
+    }
+
+    fn codegen(&self, _output: usize, inputs: &[Option<Expr>]) -> Result<Expr, InputError> {
+        let number = codegen::input(inputs, 0)?;
+        match number.scalar {
+            Scalar::U32 => Ok(codegen::op1(number, Scalar::F32, |v| {
+                format!("{} as f32", v)
+            })),
+            Scalar::F32 => Ok(number.clone()),
+            Scalar::String => Err(codegen::mismatch(0, "u32 or f32", number)),
+        }
+    }
 }
 
 #[typetag::serde]
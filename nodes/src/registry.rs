@@ -0,0 +1,396 @@
+use crate::{
+    AddNode, ConstantNode, CosNode, DivisionNode, ExpressionNode, FractalNoiseNode, GlobalNode,
+    GradientNoiseNode, GridNode, LoopTimeNode, ModuloNode, MultiplyNode, Node, OutputInfo,
+    RandomDirectionNode, RandomFloatNode, RandomIntegerNode, RangeNode, RatioNode, RepeatNode,
+    SimplexNoiseNode, SinCosNode, SineNode, ToFloatNode, ValueNoiseNode,
+};
+use serde::Serialize;
+use std::fmt::Write;
+
+// A kind of node that can be added from the palette.
+#[derive(Debug, Copy, Clone)]
+pub struct NodeEntry {
+    pub category: &'static str,
+    pub description: &'static str,
+    // Input name and what it's for. Every input the node can take should have one.
+    pub inputs: &'static [(&'static str, &'static str)],
+    pub constructor: fn() -> Box<dyn Node>,
+}
+
+impl NodeEntry {
+    pub fn create(&self) -> Box<dyn Node> {
+        (self.constructor)()
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.create().name()
+    }
+
+    pub fn doc(&self) -> NodeDoc {
+        let node = self.create();
+        let mut inputs: Vec<InputDoc> = Vec::new();
+        for group in node.inputs().groups.iter() {
+            for info in group.info.iter() {
+                match inputs.iter_mut().find(|input| input.name == info.name) {
+                    Some(input) => {
+                        if !input.types.contains(&info.ty_name) {
+                            input.types.push(info.ty_name);
+                        }
+                        input.optional &= info.optional;
+                    }
+                    None => inputs.push(InputDoc {
+                        name: info.name.clone().into_owned(),
+                        types: vec![info.ty_name],
+                        optional: info.optional,
+                        description: self.input_description(&info.name).unwrap_or_default(),
+                    }),
+                }
+            }
+        }
+        NodeDoc {
+            name: node.name(),
+            category: self.category,
+            description: self.description,
+            variadic: node.variadic(),
+            inputs,
+            outputs: node.outputs(),
+        }
+    }
+
+    pub fn input_description(&self, name: &str) -> Option<&'static str> {
+        self.inputs
+            .iter()
+            .find(|(input, _)| *input == name)
+            .map(|(_, description)| *description)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct InputDoc {
+    pub name: String,
+    pub types: Vec<&'static str>,
+    pub optional: bool,
+    pub description: &'static str,
+}
+
+// Everything known about a node, for documentation and tooling.
+#[derive(Debug, Clone, Serialize)]
+pub struct NodeDoc {
+    pub name: &'static str,
+    pub category: &'static str,
+    pub description: &'static str,
+    pub variadic: bool,
+    pub inputs: Vec<InputDoc>,
+    pub outputs: &'static [OutputInfo],
+}
+
+// The nodes on offer, kept grouped by category in the order the categories were first seen.
+// Crates that define their own nodes start from `Registry::builtin` and register on top of it.
+#[derive(Debug, Clone, Default)]
+pub struct Registry {
+    entries: Vec<NodeEntry>,
+}
+
+impl Registry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Adds a kind of node. An entry with the same name is replaced in place so that a more
+    // capable version of a node can stand in for the original.
+    pub fn register(&mut self, entry: NodeEntry) -> &mut Self {
+        let name = entry.name();
+        if let Some(existing) = self.entries.iter_mut().find(|e| e.name() == name) {
+            *existing = entry;
+        } else {
+            let index = self
+                .entries
+                .iter()
+                .rposition(|e| e.category == entry.category)
+                .map(|index| index + 1)
+                .unwrap_or(self.entries.len());
+            self.entries.insert(index, entry);
+        }
+        self
+    }
+
+    pub fn entries(&self) -> &[NodeEntry] {
+        &self.entries
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn get(&self, name: &str) -> Option<&NodeEntry> {
+        self.entries.iter().find(|entry| entry.name() == name)
+    }
+
+    pub fn categories(&self) -> Vec<&'static str> {
+        let mut categories: Vec<&'static str> = Vec::new();
+        for entry in self.entries.iter() {
+            if !categories.contains(&entry.category) {
+                categories.push(entry.category);
+            }
+        }
+        categories
+    }
+
+    pub fn catalogue(&self) -> Vec<NodeDoc> {
+        self.entries.iter().map(NodeEntry::doc).collect()
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut docs = String::new();
+        writeln!(docs, "# Nodes").unwrap();
+        let catalogue = self.catalogue();
+        for category in self.categories() {
+            writeln!(docs, "\n## {}", category).unwrap();
+            for node in catalogue.iter().filter(|node| node.category == category) {
+                writeln!(docs, "\n### {}\n\n{}", node.name, node.description).unwrap();
+                if !node.inputs.is_empty() {
+                    let any = if node.variadic { " (any number)" } else { "" };
+                    writeln!(docs, "\nInputs{}:", any).unwrap();
+                    for input in node.inputs.iter() {
+                        let optional = if input.optional { ", optional" } else { "" };
+                        writeln!(
+                            docs,
+                            "- `{}` ({}{}): {}",
+                            input.name,
+                            input.types.join(" | "),
+                            optional,
+                            input.description
+                        )
+                        .unwrap();
+                    }
+                }
+                writeln!(docs, "\nOutputs:").unwrap();
+                for output in node.outputs.iter() {
+                    writeln!(docs, "- `{}` ({})", output.name, output.ty_name).unwrap();
+                }
+            }
+        }
+        docs
+    }
+
+    // The nodes defined in this crate.
+    pub fn builtin() -> Self {
+        let mut registry = Self::new();
+        registry
+            .register(NodeEntry {
+                category: "input",
+                description: "A fixed number or piece of text.",
+                inputs: &[],
+                constructor: || Box::new(ConstantNode::Unsigned(0)),
+            })
+            .register(NodeEntry {
+                category: "input",
+                description: "The current frame number.",
+                inputs: &[],
+                constructor: || Box::new(GlobalNode),
+            })
+            .register(NodeEntry {
+                category: "input",
+                description: "How far through a loop the animation is, from 0 to 1, along with the \
+                              seconds elapsed and how many loops have gone by. Runs on real time \
+                              in the editor so it doesn't depend on the frame rate.",
+                inputs: &[(
+                    "length",
+                    "How long a loop is, in frames if it's a whole number and otherwise in seconds.",
+                )],
+                constructor: || Box::new(LoopTimeNode),
+            })
+            .register(NodeEntry {
+                category: "math",
+                description: "Adds two numbers.",
+                inputs: &[("lhs", "The first term."), ("rhs", "The second term.")],
+                constructor: || Box::new(AddNode),
+            })
+            .register(NodeEntry {
+                category: "math",
+                description: "Multiplies two numbers.",
+                inputs: &[("lhs", "The first factor."), ("rhs", "The second factor.")],
+                constructor: || Box::new(MultiplyNode),
+            })
+            .register(NodeEntry {
+                category: "math",
+                description: "Divides one number by another. Integer division by zero is zero.",
+                inputs: &[
+                    ("numerator", "The number being divided."),
+                    ("denominator", "The number to divide by."),
+                ],
+                constructor: || Box::new(DivisionNode),
+            })
+            .register(NodeEntry {
+                category: "math",
+                description: "The remainder of dividing one number by another.",
+                inputs: &[
+                    ("numerator", "The number being divided."),
+                    ("denominator", "The number to divide by."),
+                ],
+                constructor: || Box::new(ModuloNode),
+            })
+            .register(NodeEntry {
+                category: "math",
+                description: "How far the numerator is through a cycle of the denominator's \
+                              length, from 0 to 1.",
+                inputs: &[
+                    ("numerator", "The position, usually the frame number."),
+                    ("denominator", "The length of the cycle."),
+                ],
+                constructor: || Box::new(RatioNode),
+            })
+            .register(NodeEntry {
+                category: "math",
+                description: "The sine of an angle in radians.",
+                inputs: &[("number", "The angle.")],
+                constructor: || Box::new(SineNode),
+            })
+            .register(NodeEntry {
+                category: "math",
+                description: "The cosine of an angle in radians.",
+                inputs: &[("number", "The angle.")],
+                constructor: || Box::new(CosNode),
+            })
+            .register(NodeEntry {
+                category: "math",
+                description: "The sine and cosine of an angle in radians.",
+                inputs: &[("number", "The angle.")],
+                constructor: || Box::new(SinCosNode),
+            })
+            .register(NodeEntry {
+                category: "math",
+                description: "Converts whole numbers to floating point.",
+                inputs: &[("number", "The number to convert.")],
+                constructor: || Box::new(ToFloatNode),
+            })
+            .register(NodeEntry {
+                category: "math",
+                description: "A formula like `sin(t * 0.1) * 200 + 400`. Every name that isn't a \
+                              function or a constant such as `pi` becomes an input.",
+                inputs: &[("x", "The only input of the default formula.")],
+                constructor: || Box::new(ExpressionNode::default()),
+            })
+            .register(NodeEntry {
+                category: "math",
+                description: "A random number in a range for each index. The same seed and index \
+                              always give the same number.",
+                inputs: &[
+                    ("seed", "Picks a different set of numbers."),
+                    ("index", "Which number of the set, usually from a range."),
+                    ("min", "The smallest number."),
+                    ("max", "The number everything is below."),
+                ],
+                constructor: || Box::new(RandomFloatNode),
+            })
+            .register(NodeEntry {
+                category: "math",
+                description: "A random whole number in a range for each index. The same seed and \
+                              index always give the same number.",
+                inputs: &[
+                    ("seed", "Picks a different set of numbers."),
+                    ("index", "Which number of the set, usually from a range."),
+                    ("min", "The smallest number."),
+                    ("max", "The number everything is below."),
+                ],
+                constructor: || Box::new(RandomIntegerNode),
+            })
+            .register(NodeEntry {
+                category: "math",
+                description: "The x and y of a random direction one unit long for each index. The \
+                              same seed and index always give the same direction.",
+                inputs: &[
+                    ("seed", "Picks a different set of directions."),
+                    ("index", "Which direction of the set, usually from a range."),
+                ],
+                constructor: || Box::new(RandomDirectionNode),
+            })
+            .register(NodeEntry {
+                category: "math",
+                description: "Perlin noise, a value from about -1 to 1 that changes smoothly with \
+                              its coordinates. Leave y and z unconnected for 1D or 2D noise, or \
+                              use the time for z to make things wobble.",
+                inputs: &[
+                    ("x", "The first coordinate."),
+                    ("y", "The second coordinate, zero if unconnected."),
+                    ("z", "The third coordinate, zero if unconnected."),
+                    ("seed", "Picks a different pattern."),
+                    ("frequency", "How quickly the noise changes, 1 if unconnected."),
+                ],
+                constructor: || Box::new(GradientNoiseNode),
+            })
+            .register(NodeEntry {
+                category: "math",
+                description: "Simplex noise, like Perlin noise but with fewer straight lines \
+                              showing in the pattern.",
+                inputs: &[
+                    ("x", "The first coordinate."),
+                    ("y", "The second coordinate, zero if unconnected."),
+                    ("z", "The third coordinate, zero if unconnected."),
+                    ("seed", "Picks a different pattern."),
+                    ("frequency", "How quickly the noise changes, 1 if unconnected."),
+                ],
+                constructor: || Box::new(SimplexNoiseNode),
+            })
+            .register(NodeEntry {
+                category: "math",
+                description: "Value noise, a random value from -1 to 1 at every whole coordinate \
+                              blended smoothly in between. Blockier than Perlin noise.",
+                inputs: &[
+                    ("x", "The first coordinate."),
+                    ("y", "The second coordinate, zero if unconnected."),
+                    ("z", "The third coordinate, zero if unconnected."),
+                    ("seed", "Picks a different pattern."),
+                    ("frequency", "How quickly the noise changes, 1 if unconnected."),
+                ],
+                constructor: || Box::new(ValueNoiseNode),
+            })
+            .register(NodeEntry {
+                category: "math",
+                description: "Layers of Perlin noise, each twice as detailed as the last, for \
+                              rougher and more natural looking variation.",
+                inputs: &[
+                    ("x", "The first coordinate."),
+                    ("y", "The second coordinate, zero if unconnected."),
+                    ("z", "The third coordinate, zero if unconnected."),
+                    ("seed", "Picks a different pattern."),
+                    ("frequency", "How quickly the first layer changes, 1 if unconnected."),
+                    ("octaves", "How many layers there are, 4 if unconnected."),
+                    (
+                        "persistence",
+                        "How strong each layer is compared to the one before, 0.5 if unconnected.",
+                    ),
+                ],
+                constructor: || Box::new(FractalNoiseNode),
+            })
+            .register(NodeEntry {
+                category: "sequence",
+                description: "Counts from zero up to, but not including, a length.",
+                inputs: &[("length", "How many numbers to count.")],
+                constructor: || Box::new(RangeNode),
+            })
+            .register(NodeEntry {
+                category: "sequence",
+                description: "Repeats a value a number of times.",
+                inputs: &[
+                    ("count", "How many times to repeat."),
+                    ("value", "The value to repeat."),
+                ],
+                constructor: || Box::new(RepeatNode),
+            })
+            .register(NodeEntry {
+                category: "sequence",
+                description: "The x and y coordinates of every cell in a grid, row by row.",
+                inputs: &[
+                    ("width", "The number of columns."),
+                    ("height", "The number of rows."),
+                ],
+                constructor: || Box::new(GridNode),
+            });
+        registry
+    }
+}
@@ -0,0 +1,236 @@
+// Evaluates a saved graph without opening a window and prints the commands it produces each frame.
+// With `--png` or `--svg` the last frame is also drawn and saved as an image, and `--gif` saves
+// every frame as an animation.
+//
+//     headless <graph> [--frames <count>] [--start <tick>] [--end <tick>] [--loop] [--fps <rate>]
+//         [--format text|json] [--png <path>] [--svg <path>] [--gif <path>]
+//         [--size <w>x<h>] [--delay <hundredths>] [--speed <1-30>]
+//
+// The end tick is included, so `--start 1 --end 3` evaluates three frames.
+
+use demoloops_ui::{
+    command::{self, SoftwareRenderer},
+    file,
+    headless::{self, GifOptions},
+    UIGraph,
+};
+use nodes::{EvalContext, Graph};
+use std::{convert::TryFrom, path::PathBuf};
+
+const USAGE: &str = "usage: headless <graph> [--frames <count>] [--start <tick>] [--end <tick>] \
+                     [--loop] [--fps <rate>] [--format text|json] [--png <path>] [--svg <path>] \
+                     [--gif <path>] [--size <width>x<height>] [--delay <hundredths>] \
+                     [--speed <1-30>]\n\n--end is the last tick evaluated, not one past it";
+
+// How many frames `--loop` looks through for the graph to come back around.
+const MAX_LOOP: usize = 10_000;
+
+enum Format {
+    Text,
+    Json,
+}
+
+enum Ticks {
+    Count(usize),
+    End(u32),
+    Loop,
+}
+
+// What to evaluate once the ticks asked for are known to exist.
+enum Frames {
+    Range(std::ops::Range<u32>),
+    Loop,
+}
+
+struct Options {
+    path: PathBuf,
+    start: u32,
+    frames: Frames,
+    fps: f32,
+    format: Format,
+    png: Option<PathBuf>,
+    svg: Option<PathBuf>,
+    gif: Option<PathBuf>,
+    size: (u32, u32),
+    delay: u16,
+    speed: i32,
+}
+
+fn parse<T: std::str::FromStr>(arg: Option<String>, what: &str) -> Result<T, String> {
+    let arg = arg.ok_or_else(|| format!("missing {}", what))?;
+    arg.parse()
+        .map_err(|_| format!("invalid {} `{}`", what, arg))
+}
+
+fn parse_args() -> Result<Options, String> {
+    let mut path = None;
+    let mut start: u32 = 1;
+    let mut ticks = Ticks::Count(1);
+    let mut fps = nodes::FRAME_RATE;
+    let mut format = Format::Text;
+    let mut png = None;
+    let mut svg = None;
+    let mut gif = None;
+    let mut size = (1920, 1080);
+    let mut delay = GifOptions::default().delay;
+    let mut speed = GifOptions::default().speed;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--frames" => ticks = Ticks::Count(parse(args.next(), "frame count")?),
+            "--start" => start = parse(args.next(), "start tick")?,
+            "--end" => ticks = Ticks::End(parse(args.next(), "end tick")?),
+            "--loop" => ticks = Ticks::Loop,
+            "--fps" => {
+                fps = parse(args.next(), "frame rate")?;
+                if !fps.is_finite() || fps <= 0. {
+                    return Err("--fps must be more than zero".to_owned());
+                }
+            }
+            "--format" => {
+                format = match args.next().as_deref() {
+                    Some("text") => Format::Text,
+                    Some("json") => Format::Json,
+                    _ => return Err("--format must be text or json".to_owned()),
+                };
+            }
+            "--png" => png = Some(PathBuf::from(args.next().ok_or("--png needs a path")?)),
+            "--svg" => svg = Some(PathBuf::from(args.next().ok_or("--svg needs a path")?)),
+            "--gif" => gif = Some(PathBuf::from(args.next().ok_or("--gif needs a path")?)),
+            "--size" => {
+                let arg = args.next().ok_or("--size needs a width and height")?;
+                size = arg
+                    .split_once('x')
+                    .and_then(|(w, h)| Some((w.parse().ok()?, h.parse().ok()?)))
+                    .ok_or_else(|| format!("invalid size `{}`", arg))?;
+            }
+            "--delay" => delay = parse(args.next(), "delay")?,
+            "--speed" => {
+                speed = parse(args.next(), "speed")?;
+                if !(1..=30).contains(&speed) {
+                    return Err("--speed must be from 1 to 30".to_owned());
+                }
+            }
+            _ if path.is_none() && !arg.starts_with("--") => path = Some(PathBuf::from(arg)),
+            _ => return Err(format!("unexpected argument `{}`", arg)),
+        }
+    }
+    let path = path.ok_or_else(|| USAGE.to_owned())?;
+    // only checked now since `--start` can come after `--frames` or `--end`
+    let frames = match ticks {
+        Ticks::Count(count) => {
+            let end = u32::try_from(count)
+                .ok()
+                .and_then(|count| start.checked_add(count))
+                .ok_or("--start plus --frames is past the last tick")?;
+            Frames::Range(start..end)
+        }
+        Ticks::End(end) if end < start => return Err("--end can't be before --start".to_owned()),
+        Ticks::End(end) => {
+            let end = end.checked_add(1).ok_or("--end is past the last tick")?;
+            Frames::Range(start..end)
+        }
+        Ticks::Loop => Frames::Loop,
+    };
+    Ok(Options {
+        path,
+        start,
+        frames,
+        fps,
+        format,
+        png,
+        svg,
+        gif,
+        size,
+        delay,
+        speed,
+    })
+}
+
+// Files saved by the editor hold a `UIGraph` but a bare `Graph` is fine too.
+fn load(path: &std::path::Path) -> Result<Graph, file::Error> {
+    match file::load_file::<UIGraph>(path) {
+        Ok(graph) => Ok(graph.inner().clone()),
+        Err(err) => file::load_file::<Graph>(path).map_err(|_| err),
+    }
+}
+
+fn run(graph: &mut Graph, options: &Options) -> Result<Vec<headless::Frame>, String> {
+    // each tick is a fixed step of time so every run comes out the same
+    let (width, height) = options.size;
+    let ctx = EvalContext::fixed_step(options.start, options.fps).with_resolution(width, height);
+    let result = match &options.frames {
+        Frames::Range(ticks) => headless::run(graph, ticks.clone(), ctx),
+        Frames::Loop => match headless::run_loop(graph, ctx, MAX_LOOP) {
+            Ok(Some(frames)) => Ok(frames),
+            Ok(None) => return Err(format!("no loop found in {} frames", MAX_LOOP)),
+            Err(err) => Err(err),
+        },
+    };
+    result.map_err(|err| err.to_string())
+}
+
+fn save(frames: &[headless::Frame], options: &Options) -> Result<(), String> {
+    let (width, height) = options.size;
+    let frame = match frames.last() {
+        Some(frame) => frame,
+        None => return Ok(()),
+    };
+    if let Some(path) = &options.png {
+        let mut renderer = SoftwareRenderer::new(width, height);
+        renderer.render(&frame.commands);
+        renderer
+            .save_png(path)
+            .map_err(|err| format!("{}: {}", path.display(), err))?;
+    }
+    if let Some(path) = &options.svg {
+        let svg = command::to_svg(&frame.commands, width, height);
+        std::fs::write(path, svg).map_err(|err| format!("{}: {}", path.display(), err))?;
+    }
+    if let Some(path) = &options.gif {
+        let size = |v: u32| u16::try_from(v).map_err(|_| "the size is too big for a GIF");
+        let settings = GifOptions {
+            width: size(width)?,
+            height: size(height)?,
+            delay: options.delay,
+            speed: options.speed,
+        };
+        std::fs::File::create(path)
+            .map_err(gif::EncodingError::from)
+            .and_then(|file| headless::write_gif(frames, settings, std::io::BufWriter::new(file)))
+            .map_err(|err| format!("{}: {}", path.display(), err))?;
+    }
+    Ok(())
+}
+
+fn main() {
+    let options = parse_args().unwrap_or_else(|err| {
+        eprintln!("{}", err);
+        std::process::exit(2);
+    });
+    let result = load(&options.path)
+        .map_err(|err| err.to_string())
+        .and_then(|mut graph| {
+            graph.validate().map_err(|err| err.to_string())?;
+            run(&mut graph, &options)
+        });
+    match result {
+        Ok(frames) => {
+            match options.format {
+                Format::Text => print!("{}", headless::to_text(&frames)),
+                Format::Json => {
+                    let json = headless::to_json(&frames);
+                    println!("{}", serde_json::to_string_pretty(&json).unwrap());
+                }
+            }
+            if let Err(err) = save(&frames, &options) {
+                eprintln!("{}", err);
+                std::process::exit(1);
+            }
+        }
+        Err(err) => {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }
+    }
+}
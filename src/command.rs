@@ -1,16 +1,22 @@
+mod serialize;
 mod shader;
+mod software;
+mod svg;
 
+use serde::{Deserialize, Serialize};
 pub use shader::Shader;
+pub use software::SoftwareRenderer;
 use solstice_2d::{
     solstice::{image::Image, Context},
     Color, Draw, Graphics, GraphicsLock, PerlinTextureSettings, Rectangle, RegularPolygon,
     Transform3D,
 };
+pub use svg::{to_svg, SvgRenderer};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum Geometry {
-    Rectangle(Rectangle),
-    RegularPolygon(RegularPolygon),
+    Rectangle(#[serde(with = "serialize::RectangleDef")] Rectangle),
+    RegularPolygon(#[serde(with = "serialize::RegularPolygonDef")] RegularPolygon),
 }
 
 impl Into<Geometry> for Rectangle {
@@ -25,9 +31,9 @@ impl Into<Geometry> for RegularPolygon {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum Texture {
-    Noise(PerlinTextureSettings),
+    Noise(#[serde(with = "serialize::perlin")] PerlinTextureSettings),
     Default,
 }
 
@@ -40,10 +46,12 @@ impl Into<Texture> for Option<PerlinTextureSettings> {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DrawCommand {
     pub geometry: Geometry,
+    #[serde(with = "serialize::transform")]
     pub transform: Transform3D,
+    #[serde(with = "serialize::ColorDef")]
     pub color: Color,
     pub texture: Texture,
     pub shader: Option<Shader>,
@@ -82,8 +90,9 @@ impl DrawCommand {
     }
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
 pub struct ClearCommand {
+    #[serde(with = "serialize::ColorDef")]
     color: Color,
 }
 
@@ -129,7 +138,7 @@ impl ResourcesCache {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum Command {
     Draw(DrawCommand),
     Clear(ClearCommand),
@@ -150,54 +159,137 @@ impl Command {
     }
 
     pub fn execute(&self, gfx: &mut GraphicsLock, cache: &ResourcesCache) {
+        self.render(&mut GraphicsRenderer { gfx, cache });
+    }
+
+    pub fn render<R: Renderer + ?Sized>(&self, renderer: &mut R) {
         match self {
-            Command::Draw(command) => {
-                let shader = command
-                    .shader
-                    .as_ref()
-                    .and_then(|v| cache.shaders.get(&v.source).cloned());
-                gfx.set_shader(shader);
-
-                match &command.texture {
-                    Texture::Default => match command.geometry {
-                        Geometry::Rectangle(geometry) => gfx.draw_with_color_and_transform(
-                            geometry,
-                            command.color,
-                            command.transform,
-                        ),
-                        Geometry::RegularPolygon(geometry) => gfx.draw_with_color_and_transform(
-                            geometry,
-                            command.color,
-                            command.transform,
-                        ),
-                    },
-                    Texture::Noise(settings) => {
-                        let texture = cache
-                            .textures
-                            .get(settings)
-                            .expect("Cache should be warmed prior to execution.")
-                            .clone();
-                        match command.geometry {
-                            Geometry::Rectangle(geometry) => gfx.image_with_color_and_transform(
-                                geometry,
-                                texture,
-                                command.color,
-                                command.transform,
-                            ),
-                            Geometry::RegularPolygon(geometry) => gfx
-                                .image_with_color_and_transform(
-                                    geometry,
-                                    texture,
-                                    command.color,
-                                    command.transform,
-                                ),
-                        }
-                    }
+            Command::Draw(command) => renderer.draw(command),
+            Command::Clear(command) => renderer.clear(command.color),
+        }
+    }
+}
+
+// Something that commands can be drawn to.
+pub trait Renderer {
+    fn clear(&mut self, color: Color);
+    fn draw(&mut self, command: &DrawCommand);
+}
+
+// Draws through solstice. The cache needs to be warmed with the commands before they're drawn.
+pub struct GraphicsRenderer<'a, 'b, 'c> {
+    pub gfx: &'a mut GraphicsLock<'b, 'c>,
+    pub cache: &'a ResourcesCache,
+}
+
+impl Renderer for GraphicsRenderer<'_, '_, '_> {
+    fn clear(&mut self, color: Color) {
+        self.gfx.clear(color);
+    }
+
+    fn draw(&mut self, command: &DrawCommand) {
+        let Self { gfx, cache } = self;
+        let shader = command
+            .shader
+            .as_ref()
+            .and_then(|v| cache.shaders.get(&v.source).cloned());
+        gfx.set_shader(shader);
+
+        match &command.texture {
+            Texture::Default => match command.geometry {
+                Geometry::Rectangle(geometry) => {
+                    gfx.draw_with_color_and_transform(geometry, command.color, command.transform)
+                }
+                Geometry::RegularPolygon(geometry) => {
+                    gfx.draw_with_color_and_transform(geometry, command.color, command.transform)
+                }
+            },
+            Texture::Noise(settings) => {
+                let texture = cache
+                    .textures
+                    .get(settings)
+                    .expect("Cache should be warmed prior to execution.")
+                    .clone();
+                match command.geometry {
+                    Geometry::Rectangle(geometry) => gfx.image_with_color_and_transform(
+                        geometry,
+                        texture,
+                        command.color,
+                        command.transform,
+                    ),
+                    Geometry::RegularPolygon(geometry) => gfx.image_with_color_and_transform(
+                        geometry,
+                        texture,
+                        command.color,
+                        command.transform,
+                    ),
                 }
             }
-            Command::Clear(command) => {
-                gfx.clear(command.color);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solstice_2d::{solstice::shader::RawUniformValue, Rad};
+
+    fn columns(transform: &Transform3D) -> [[f32; 4]; 4] {
+        mint::ColumnMatrix4::from(transform).into()
+    }
+
+    #[test]
+    fn transform() {
+        let transforms = [
+            Transform3D::default(),
+            Transform3D::translation(1., -2., 3.),
+            Transform3D::rotation(Rad(0.5), Rad(-1.), Rad(2.)),
+            Transform3D::rotation(Rad(0.), Rad(std::f32::consts::FRAC_PI_2), Rad(1.)),
+            Transform3D::translation(10., 20., 0.)
+                * Transform3D::rotation(Rad(0.), Rad(0.), Rad(0.75))
+                * Transform3D::scale(2., -3., 1.),
+        ];
+        for transform in transforms.iter() {
+            let expected = columns(transform);
+            let actual = columns(&serialize::transform::from_columns(expected));
+            for (expected, actual) in expected.iter().flatten().zip(actual.iter().flatten()) {
+                assert!((expected - actual).abs() < 1e-5, "{:?}", transform);
             }
         }
     }
+
+    #[test]
+    fn round_trip() {
+        let mut uniforms = std::collections::HashMap::new();
+        uniforms.insert("offset".to_owned(), RawUniformValue::Float(0.5));
+        uniforms.insert("count".to_owned(), RawUniformValue::SignedInt(3));
+        let commands = vec![
+            Command::Clear(ClearCommand::new(Color::new(0., 0., 0., 1.))),
+            Command::Draw(DrawCommand::with_shader(
+                Rectangle::new(0., 0., 10., 20.),
+                Transform3D::translation(5., 0., 0.),
+                Color::new(1., 0.5, 0.25, 1.),
+                None,
+                Shader {
+                    source: "void main() {}".to_owned(),
+                    uniforms,
+                },
+            )),
+            Command::Draw(DrawCommand::new(
+                RegularPolygon::new(1., 2., 6, 10.),
+                Transform3D::default(),
+                Color::new(1., 1., 1., 1.),
+                Some(PerlinTextureSettings::default()),
+            )),
+        ];
+
+        let json = serde_json::to_string(&commands).unwrap();
+        assert!(json.starts_with(
+            r#"[{"Clear":{"color":{"red":0.0,"green":0.0,"blue":0.0,"alpha":1.0}}},{"Draw":{"geometry":{"Rectangle":{"x":0.0,"y":0.0,"width":10.0,"height":20.0}},"transform":[[1.0,0.0,0.0,0.0],[0.0,1.0,0.0,0.0],[0.0,0.0,1.0,0.0],[5.0,0.0,0.0,1.0]]"#
+        ));
+        assert!(json.contains(r#""uniforms":{"count":{"SignedInt":3},"offset":{"Float":0.5}}"#));
+
+        let loaded: Vec<Command> = serde_json::from_str(&json).unwrap();
+        assert_eq!(json, serde_json::to_string(&loaded).unwrap());
+    }
 }
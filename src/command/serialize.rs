@@ -0,0 +1,170 @@
+// Serde support for the solstice types held by commands, used through `#[serde(with = ..)]`.
+
+use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+use solstice_2d::{
+    solstice::shader::RawUniformValue, Color, PerlinTextureSettings, Rad, Rectangle,
+    RegularPolygon, Transform3D,
+};
+use std::collections::{BTreeMap, HashMap};
+
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "Color")]
+pub struct ColorDef {
+    pub red: f32,
+    pub green: f32,
+    pub blue: f32,
+    pub alpha: f32,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "Rectangle")]
+pub struct RectangleDef {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "RegularPolygon")]
+pub struct RegularPolygonDef {
+    pub x: f32,
+    pub y: f32,
+    pub vertex_count: u32,
+    pub radius: f32,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "RawUniformValue")]
+pub enum RawUniformValueDef {
+    SignedInt(i32),
+    Float(f32),
+    Mat2(mint::ColumnMatrix2<f32>),
+    Mat3(mint::ColumnMatrix3<f32>),
+    Mat4(mint::ColumnMatrix4<f32>),
+    Vec2(mint::Vector2<f32>),
+    Vec3(mint::Vector3<f32>),
+    Vec4(mint::Vector4<f32>),
+    IntVec2(mint::Vector2<i32>),
+    IntVec3(mint::Vector3<i32>),
+    IntVec4(mint::Vector4<i32>),
+}
+
+// `Transform3D` only exposes its matrix so that's what is saved. Loading splits the matrix back
+// into a translation, a rotation and a scale.
+pub mod transform {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(transform: &Transform3D, s: S) -> Result<S::Ok, S::Error> {
+        mint::ColumnMatrix4::from(transform).serialize(s)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Transform3D, D::Error> {
+        mint::ColumnMatrix4::<f32>::deserialize(d).map(|m| from_columns(m.into()))
+    }
+
+    pub fn from_columns([x, y, z, w]: [[f32; 4]; 4]) -> Transform3D {
+        let length = |c: [f32; 4]| (c[0] * c[0] + c[1] * c[1] + c[2] * c[2]).sqrt();
+        let mut scale = [length(x), length(y), length(z)];
+        // a reflection can't be represented by a rotation so it goes into the scale
+        let determinant = x[0] * (y[1] * z[2] - z[1] * y[2]) - y[0] * (x[1] * z[2] - z[1] * x[2])
+            + z[0] * (x[1] * y[2] - y[1] * x[2]);
+        if determinant < 0. {
+            scale[0] = -scale[0];
+        }
+        let axis = |c: [f32; 4], scale: f32| {
+            if scale == 0. {
+                [0.; 3]
+            } else {
+                [c[0] / scale, c[1] / scale, c[2] / scale]
+            }
+        };
+        let (x_axis, y_axis, z_axis) = (axis(x, scale[0]), axis(y, scale[1]), axis(z, scale[2]));
+
+        // the inverse of `UnitQuaternion::from_euler_angles`, which rotates by yaw * pitch * roll
+        let sin_pitch = -x_axis[2].clamp(-1., 1.);
+        let pitch = sin_pitch.asin();
+        let (roll, yaw) = if sin_pitch.abs() < 1. - f32::EPSILON {
+            (y_axis[2].atan2(z_axis[2]), x_axis[1].atan2(x_axis[0]))
+        } else {
+            (0., (-y_axis[0]).atan2(y_axis[1]))
+        };
+
+        Transform3D::translation(w[0], w[1], w[2])
+            * Transform3D::rotation(Rad(roll), Rad(pitch), Rad(yaw))
+            * Transform3D::scale(scale[0], scale[1], scale[2])
+    }
+}
+
+// `PerlinTextureSettings::attenuation` is a `NotNan<f32>` which isn't serializable by itself.
+pub mod perlin {
+    use super::*;
+
+    #[derive(Serialize, Deserialize)]
+    struct Settings {
+        seed: i32,
+        width: usize,
+        height: usize,
+        period: u32,
+        levels: u32,
+        attenuation: f32,
+        color: bool,
+    }
+
+    pub fn serialize<S: Serializer>(v: &PerlinTextureSettings, s: S) -> Result<S::Ok, S::Error> {
+        Settings {
+            seed: v.seed,
+            width: v.width,
+            height: v.height,
+            period: v.period,
+            levels: v.levels,
+            attenuation: *v.attenuation,
+            color: v.color,
+        }
+        .serialize(s)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<PerlinTextureSettings, D::Error> {
+        let v = Settings::deserialize(d)?;
+        let attenuation = std::convert::TryInto::try_into(v.attenuation)
+            .map_err(|_| D::Error::custom("attenuation can't be NaN"))?;
+        Ok(PerlinTextureSettings {
+            seed: v.seed,
+            width: v.width,
+            height: v.height,
+            period: v.period,
+            levels: v.levels,
+            attenuation,
+            color: v.color,
+        })
+    }
+}
+
+// Uniforms are saved sorted by name so that the same shader always serializes the same way.
+pub mod uniforms {
+    use super::*;
+
+    #[derive(Serialize, Deserialize)]
+    struct Uniform(#[serde(with = "RawUniformValueDef")] RawUniformValue);
+
+    pub fn serialize<S: Serializer>(
+        uniforms: &HashMap<String, RawUniformValue>,
+        s: S,
+    ) -> Result<S::Ok, S::Error> {
+        uniforms
+            .iter()
+            .map(|(name, value)| (name, Uniform(*value)))
+            .collect::<BTreeMap<_, _>>()
+            .serialize(s)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        d: D,
+    ) -> Result<HashMap<String, RawUniformValue>, D::Error> {
+        let uniforms = HashMap::<String, Uniform>::deserialize(d)?;
+        Ok(uniforms
+            .into_iter()
+            .map(|(name, Uniform(value))| (name, value))
+            .collect())
+    }
+}
@@ -1,7 +1,8 @@
 use solstice_2d::solstice::shader::RawUniformValue;
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
 pub struct Shader {
     pub source: String,
+    #[serde(with = "super::serialize::uniforms")]
     pub uniforms: std::collections::HashMap<String, RawUniformValue>,
 }
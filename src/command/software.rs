@@ -0,0 +1,488 @@
+// Draws commands into an RGBA buffer on the CPU so that graphs can be looked at, and compared,
+// without a GPU. Shaders can't run here so draws that have one are filled as if they didn't.
+
+use super::{Command, DrawCommand, Geometry, Renderer, Texture};
+use solstice_2d::{Color, PerlinTextureSettings, Transform3D};
+use std::collections::HashMap;
+
+pub struct SoftwareRenderer {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+    textures: HashMap<PerlinTextureSettings, Option<NoiseTexture>>,
+}
+
+impl SoftwareRenderer {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![0; width as usize * height as usize * 4],
+            textures: Default::default(),
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    // Rows of RGBA from the top left.
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    pub fn pixel(&self, x: u32, y: u32) -> [u8; 4] {
+        let index = (y * self.width + x) as usize * 4;
+        let mut pixel = [0; 4];
+        pixel.copy_from_slice(&self.pixels[index..index + 4]);
+        pixel
+    }
+
+    pub fn render(&mut self, commands: &[Command]) {
+        for command in commands {
+            command.render(self);
+        }
+    }
+
+    pub fn write_png<W: std::io::Write>(&self, writer: W) -> Result<(), png::EncodingError> {
+        let mut encoder = png::Encoder::new(writer, self.width, self.height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder.write_header()?.write_image_data(&self.pixels)
+    }
+
+    pub fn to_png(&self) -> Vec<u8> {
+        let mut png = Vec::new();
+        self.write_png(&mut png).unwrap();
+        png
+    }
+
+    pub fn save_png<P: AsRef<std::path::Path>>(&self, path: P) -> Result<(), png::EncodingError> {
+        let file = std::fs::File::create(path)?;
+        self.write_png(std::io::BufWriter::new(file))
+    }
+}
+
+impl Renderer for SoftwareRenderer {
+    fn clear(&mut self, color: Color) {
+        let color = to_bytes([color.red, color.green, color.blue, color.alpha]);
+        for pixel in self.pixels.chunks_exact_mut(4) {
+            pixel.copy_from_slice(&color);
+        }
+    }
+
+    fn draw(&mut self, command: &DrawCommand) {
+        let texture = match &command.texture {
+            Texture::Default => None,
+            Texture::Noise(settings) => self
+                .textures
+                .entry(*settings)
+                .or_insert_with(|| NoiseTexture::new(*settings))
+                .as_ref(),
+        };
+        let vertices = vertices(&command.geometry, &command.transform);
+        let color = [
+            command.color.red,
+            command.color.green,
+            command.color.blue,
+            command.color.alpha,
+        ];
+        let mut canvas = Canvas {
+            width: self.width,
+            height: self.height,
+            pixels: &mut self.pixels,
+        };
+        canvas.fill(&vertices, color, texture);
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+struct Vertex {
+    position: [f32; 2],
+    uv: [f32; 2],
+}
+
+// The corners of the shape in pixels, laid out the same way solstice builds its meshes.
+fn vertices(geometry: &Geometry, transform: &Transform3D) -> Vec<Vertex> {
+    let [x, y, _, w]: [[f32; 4]; 4] = mint::ColumnMatrix4::from(transform).into();
+    let vertex = |px: f32, py: f32, uv: [f32; 2]| Vertex {
+        position: [x[0] * px + y[0] * py + w[0], x[1] * px + y[1] * py + w[1]],
+        uv,
+    };
+    match *geometry {
+        Geometry::Rectangle(r) => vec![
+            vertex(r.x, r.y, [0., 0.]),
+            vertex(r.x, r.y + r.height, [0., 1.]),
+            vertex(r.x + r.width, r.y + r.height, [1., 1.]),
+            vertex(r.x + r.width, r.y, [1., 0.]),
+        ],
+        Geometry::RegularPolygon(p) => {
+            let angle_shift = std::f32::consts::PI * 2. / p.vertex_count as f32;
+            (0..p.vertex_count)
+                .map(|i| {
+                    let (s, c) = (angle_shift * i as f32).sin_cos();
+                    let uv = [(c + 1.) / 2., (s + 1.) / 2.];
+                    vertex(p.x + p.radius * c, p.y + p.radius * s, uv)
+                })
+                .collect()
+        }
+    }
+}
+
+// Twice the signed area of the triangle, positive when `c` is clockwise from `a -> b` on screen.
+fn edge(a: [f32; 2], b: [f32; 2], c: [f32; 2]) -> f32 {
+    (b[0] - a[0]) * (c[1] - a[1]) - (b[1] - a[1]) * (c[0] - a[0])
+}
+
+fn to_bytes(color: [f32; 4]) -> [u8; 4] {
+    let mut bytes = [0; 4];
+    for (byte, channel) in bytes.iter_mut().zip(color.iter()) {
+        *byte = (channel.clamp(0., 1.) * 255.).round() as u8;
+    }
+    bytes
+}
+
+struct Canvas<'a> {
+    width: u32,
+    height: u32,
+    pixels: &'a mut [u8],
+}
+
+impl Canvas<'_> {
+    // Fills a convex polygon, sampling at pixel centres.
+    fn fill(&mut self, vertices: &[Vertex], color: [f32; 4], texture: Option<&NoiseTexture>) {
+        if vertices.len() < 3 {
+            return;
+        }
+        let area = (1..vertices.len() - 1)
+            .map(|i| {
+                edge(
+                    vertices[0].position,
+                    vertices[i].position,
+                    vertices[i + 1].position,
+                )
+            })
+            .sum::<f32>();
+        if area == 0. || !area.is_finite() {
+            return;
+        }
+        let winding = area.signum();
+
+        let (mut min, mut max) = ([f32::MAX; 2], [f32::MIN; 2]);
+        for vertex in vertices {
+            for axis in 0..2 {
+                min[axis] = min[axis].min(vertex.position[axis]);
+                max[axis] = max[axis].max(vertex.position[axis]);
+            }
+        }
+        let x_range = min[0].floor().max(0.) as u32..(max[0].ceil().max(0.) as u32).min(self.width);
+        let y_range =
+            min[1].floor().max(0.) as u32..(max[1].ceil().max(0.) as u32).min(self.height);
+
+        for y in y_range {
+            for x in x_range.clone() {
+                let point = [x as f32 + 0.5, y as f32 + 0.5];
+                let inside = (0..vertices.len()).all(|i| {
+                    let next = vertices[(i + 1) % vertices.len()].position;
+                    edge(vertices[i].position, next, point) * winding >= 0.
+                });
+                if !inside {
+                    continue;
+                }
+                let mut src = color;
+                if let Some(texture) = texture {
+                    let texel = texture.sample(uv(vertices, point));
+                    for (channel, texel) in src.iter_mut().zip(texel.iter()) {
+                        *channel *= texel;
+                    }
+                }
+                self.blend(x, y, src);
+            }
+        }
+    }
+
+    // Straight alpha "source over", the same as solstice's default blend mode.
+    fn blend(&mut self, x: u32, y: u32, src: [f32; 4]) {
+        let index = (y * self.width + x) as usize * 4;
+        let dst = &mut self.pixels[index..index + 4];
+        let alpha = src[3].clamp(0., 1.);
+        let mut out = [0.; 4];
+        for channel in 0..3 {
+            out[channel] = src[channel] * alpha + dst[channel] as f32 / 255. * (1. - alpha);
+        }
+        out[3] = alpha + dst[3] as f32 / 255. * (1. - alpha);
+        dst.copy_from_slice(&to_bytes(out));
+    }
+}
+
+// The texture coordinate at a point inside the polygon, interpolated across the triangle of its
+// fan that holds the point.
+fn uv(vertices: &[Vertex], point: [f32; 2]) -> [f32; 2] {
+    let a = vertices[0];
+    for i in 1..vertices.len() - 1 {
+        let (b, c) = (vertices[i], vertices[i + 1]);
+        let area = edge(a.position, b.position, c.position);
+        if area == 0. {
+            continue;
+        }
+        let wa = edge(b.position, c.position, point) / area;
+        let wb = edge(c.position, a.position, point) / area;
+        let wc = 1. - wa - wb;
+        if wa >= -1e-4 && wb >= -1e-4 && wc >= -1e-4 {
+            return [
+                a.uv[0] * wa + b.uv[0] * wb + c.uv[0] * wc,
+                a.uv[1] * wa + b.uv[1] * wb + c.uv[1] * wc,
+            ];
+        }
+    }
+    a.uv
+}
+
+// The CPU side of `solstice_2d::create_perlin_texture`.
+struct NoiseTexture {
+    width: usize,
+    height: usize,
+    channels: usize,
+    bytes: Vec<u8>,
+}
+
+impl NoiseTexture {
+    // Settings that couldn't make a texture leave the draw untextured.
+    fn new(settings: PerlinTextureSettings) -> Option<Self> {
+        if settings.width == 0 || settings.height == 0 || settings.period == 0 {
+            return None;
+        }
+        Some(Self {
+            width: settings.width,
+            height: settings.height,
+            channels: if settings.color { 3 } else { 1 },
+            bytes: perlin::bytes(settings),
+        })
+    }
+
+    // Nearest texel, repeating outside of 0..1.
+    fn sample(&self, [u, v]: [f32; 2]) -> [f32; 4] {
+        let wrap = |t: f32, size: usize| ((t.rem_euclid(1.) * size as f32) as usize).min(size - 1);
+        let (x, y) = (wrap(u, self.width), wrap(v, self.height));
+        let index = (y * self.width + x) * self.channels;
+        let texel = |channel: usize| self.bytes[index + channel] as f32 / 255.;
+        if self.channels == 3 {
+            [texel(0), texel(1), texel(2), 1.]
+        } else {
+            [texel(0), texel(0), texel(0), 1.]
+        }
+    }
+}
+
+// A port of solstice-2d's noise texture generation which isn't public.
+mod perlin {
+    use solstice_2d::PerlinTextureSettings;
+
+    struct Random {
+        seed: i32,
+    }
+
+    impl Random {
+        const M: i32 = i32::MAX;
+        const A: i32 = 16807;
+        const Q: i32 = 127773;
+        const R: i32 = 2836;
+
+        fn with_seed(seed: i32) -> Self {
+            let seed = if seed <= 0 {
+                -(seed % (i32::MAX - 1)) + 1
+            } else {
+                seed.min(i32::MAX - 1)
+            };
+            Self { seed }
+        }
+
+        fn next(&mut self) -> f32 {
+            let res = Self::A * (self.seed % Self::Q) - Self::R * (self.seed / Self::Q);
+            self.seed = if res <= 0 { res + Self::M } else { res };
+            self.seed as f32 / Self::M as f32
+        }
+    }
+
+    struct Sampler {
+        width: usize,
+        height: usize,
+        gradients: Vec<[f32; 2]>,
+    }
+
+    impl Sampler {
+        fn new(width: usize, height: usize, seed: i32) -> Self {
+            let mut rng = Random::with_seed(seed);
+            let gradients = (0..width * height)
+                .map(|_| {
+                    let (x, y) = (rng.next() * std::f32::consts::PI * 2.).sin_cos();
+                    [x, y]
+                })
+                .collect();
+            Self {
+                width,
+                height,
+                gradients,
+            }
+        }
+
+        fn dot(&self, x: usize, y: usize, vx: f32, vy: f32) -> f32 {
+            let [wx, wy] = self.gradients[x + y * self.width];
+            wx * vx + wy * vy
+        }
+
+        fn get(&self, x: f32, y: f32) -> f32 {
+            let lerp = |a: f32, b: f32, t: f32| a + t * (b - a);
+            let s_curve = |t: f32| t * t * (3. - 2. * t);
+
+            let (x0, y0) = (x.trunc() as usize, y.trunc() as usize);
+            let (fx, fy) = (x.fract(), y.fract());
+            let x1 = if x0 == self.width - 1 { 0 } else { x0 + 1 };
+            let y1 = if y0 == self.height - 1 { 0 } else { y0 + 1 };
+
+            let v00 = self.dot(x0, y0, fx, fy);
+            let v10 = self.dot(x1, y0, fx - 1., fy);
+            let v01 = self.dot(x0, y1, fx, fy - 1.);
+            let v11 = self.dot(x1, y1, fx - 1., fy - 1.);
+
+            let vx0 = lerp(v00, v10, s_curve(fx));
+            let vx1 = lerp(v01, v11, s_curve(fx));
+            lerp(vx0, vx1, s_curve(fy))
+        }
+    }
+
+    pub fn bytes(settings: PerlinTextureSettings) -> Vec<u8> {
+        let PerlinTextureSettings {
+            seed,
+            width,
+            height,
+            period,
+            levels,
+            attenuation,
+            color,
+        } = settings;
+        let channels = if color { 3 } else { 1 };
+        let mut raster = vec![0f32; width * height * channels];
+        for channel in 0..channels {
+            let mut period_inv = 1. / period as f32;
+            let mut freq_inv = 1f32;
+            let mut atten = 1.;
+            let mut weight = 0f32;
+
+            for level in 0..levels {
+                let sampler = Sampler::new(
+                    (width as f32 * period_inv).ceil() as usize,
+                    (height as f32 * period_inv).ceil() as usize,
+                    seed * 100 + channel as i32 * 10 + level as i32,
+                );
+                for y in 0..height {
+                    for x in 0..width {
+                        let value = sampler.get(x as f32 * period_inv, y as f32 * period_inv);
+                        raster[(x + y * width) * channels + channel] +=
+                            value * freq_inv.powf(atten);
+                    }
+                }
+                weight += freq_inv.powf(atten);
+                freq_inv *= 0.5;
+                period_inv *= 2.;
+                atten *= *attenuation;
+            }
+
+            for value in raster.iter_mut().skip(channel).step_by(channels) {
+                *value /= weight;
+            }
+        }
+        raster
+            .into_iter()
+            .map(|value| (((value + 1.) / 2.) * 255.).round() as u8)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command::ClearCommand;
+    use solstice_2d::{Rad, Rectangle, RegularPolygon};
+
+    fn draw<G: Into<Geometry>>(geometry: G, transform: Transform3D, color: Color) -> Command {
+        Command::Draw(DrawCommand::new(geometry, transform, color, None))
+    }
+
+    #[test]
+    fn shapes() {
+        let mut renderer = SoftwareRenderer::new(20, 10);
+        renderer.render(&[
+            Command::Clear(ClearCommand::new(Color::new(0., 0., 1., 1.))),
+            draw(
+                Rectangle::new(0., 0., 4., 2.),
+                Transform3D::translation(2., 3., 0.),
+                Color::new(1., 0., 0., 1.),
+            ),
+            draw(
+                RegularPolygon::new(0., 0., 4, 1.),
+                Transform3D::translation(15., 5., 0.) * Transform3D::scale(3., 3., 1.),
+                Color::new(0., 1., 0., 0.5),
+            ),
+        ]);
+
+        assert_eq!([0, 0, 255, 255], renderer.pixel(0, 0));
+        assert_eq!([255, 0, 0, 255], renderer.pixel(2, 3));
+        assert_eq!([255, 0, 0, 255], renderer.pixel(5, 4));
+        assert_eq!([0, 0, 255, 255], renderer.pixel(6, 4));
+        assert_eq!([0, 0, 255, 255], renderer.pixel(5, 5));
+        // a diamond blended over the background
+        assert_eq!([0, 128, 128, 255], renderer.pixel(15, 5));
+        assert_eq!([0, 128, 128, 255], renderer.pixel(16, 4));
+        assert_eq!([0, 0, 255, 255], renderer.pixel(17, 2));
+
+        // a quarter turn around the origin swings the rectangle down to the left of it
+        let mut renderer = SoftwareRenderer::new(10, 10);
+        renderer.render(&[draw(
+            Rectangle::new(0., 0., 4., 2.),
+            Transform3D::translation(5., 5., 0.)
+                * Transform3D::rotation(Rad(0.), Rad(0.), Rad(std::f32::consts::FRAC_PI_2)),
+            Color::new(1., 1., 1., 1.),
+        )]);
+        assert_eq!([255; 4], renderer.pixel(3, 8));
+        assert_eq!([0; 4], renderer.pixel(5, 5));
+        assert_eq!([0; 4], renderer.pixel(2, 5));
+    }
+
+    #[test]
+    fn noise() {
+        // one texel per pixel gives back the texture itself
+        let settings = PerlinTextureSettings {
+            seed: 0,
+            width: 4,
+            height: 4,
+            period: 2,
+            levels: 1,
+            attenuation: std::convert::TryInto::try_into(0.0).unwrap(),
+            color: false,
+        };
+        let mut renderer = SoftwareRenderer::new(4, 4);
+        renderer.render(&[Command::Draw(DrawCommand::new(
+            Rectangle::new(0., 0., 4., 4.),
+            Transform3D::default(),
+            Color::new(1., 1., 1., 1.),
+            Some(settings),
+        ))]);
+        let expected = [
+            128, 104, 128, 151, 158, 137, 180, 201, 128, 87, 128, 168, 97, 54, 75, 118,
+        ];
+        let actual = renderer
+            .pixels()
+            .chunks(4)
+            .map(|p| p[0])
+            .collect::<Vec<_>>();
+        assert_eq!(&expected[..], &actual[..]);
+
+        let png = renderer.to_png();
+        assert_eq!(b"\x89PNG", &png[..4]);
+    }
+}
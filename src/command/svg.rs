@@ -0,0 +1,176 @@
+// Writes commands out as an SVG document. Noise textures and shaders have no SVG equivalent so
+// those draws keep only their colour.
+
+use super::{Command, DrawCommand, Geometry, Renderer};
+use solstice_2d::{Color, Transform3D};
+use std::fmt::Write;
+
+pub struct SvgRenderer {
+    width: u32,
+    height: u32,
+    body: String,
+}
+
+impl SvgRenderer {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            body: String::new(),
+        }
+    }
+
+    pub fn render(&mut self, commands: &[Command]) {
+        for command in commands {
+            command.render(self);
+        }
+    }
+
+    pub fn to_svg(&self) -> String {
+        format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" \
+             viewBox=\"0 0 {w} {h}\">\n{body}</svg>\n",
+            w = self.width,
+            h = self.height,
+            body = self.body
+        )
+    }
+}
+
+pub fn to_svg(commands: &[Command], width: u32, height: u32) -> String {
+    let mut renderer = SvgRenderer::new(width, height);
+    renderer.render(commands);
+    renderer.to_svg()
+}
+
+impl Renderer for SvgRenderer {
+    // Everything drawn so far would be covered up so it's dropped.
+    fn clear(&mut self, color: Color) {
+        self.body.clear();
+        writeln!(
+            self.body,
+            "  <rect width=\"100%\" height=\"100%\"{}/>",
+            fill(color)
+        )
+        .unwrap();
+    }
+
+    fn draw(&mut self, command: &DrawCommand) {
+        let transform = transform(&command.transform);
+        let fill = fill(command.color);
+        match command.geometry {
+            Geometry::Rectangle(r) => {
+                let (x, width) = if r.width < 0. {
+                    (r.x + r.width, -r.width)
+                } else {
+                    (r.x, r.width)
+                };
+                let (y, height) = if r.height < 0. {
+                    (r.y + r.height, -r.height)
+                } else {
+                    (r.y, r.height)
+                };
+                writeln!(
+                    self.body,
+                    "  <rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\"{}{}/>",
+                    x, y, width, height, transform, fill
+                )
+                .unwrap();
+            }
+            Geometry::RegularPolygon(p) => {
+                let angle_shift = std::f32::consts::PI * 2. / p.vertex_count as f32;
+                let points = (0..p.vertex_count)
+                    .map(|i| {
+                        let (s, c) = (angle_shift * i as f32).sin_cos();
+                        format!("{},{}", p.x + p.radius * c, p.y + p.radius * s)
+                    })
+                    .collect::<Vec<_>>();
+                writeln!(
+                    self.body,
+                    "  <polygon points=\"{}\"{}{}/>",
+                    points.join(" "),
+                    transform,
+                    fill
+                )
+                .unwrap();
+            }
+        }
+    }
+}
+
+// The 2D part of the matrix. Anything along z is lost.
+fn transform(transform: &Transform3D) -> String {
+    let [x, y, _, w]: [[f32; 4]; 4] = mint::ColumnMatrix4::from(transform).into();
+    let matrix = [x[0], x[1], y[0], y[1], w[0], w[1]];
+    if matrix == [1., 0., 0., 1., 0., 0.] {
+        return String::new();
+    }
+    let matrix = matrix.iter().map(f32::to_string).collect::<Vec<_>>();
+    format!(" transform=\"matrix({})\"", matrix.join(" "))
+}
+
+fn fill(color: Color) -> String {
+    let channel = |c: f32| (c.clamp(0., 1.) * 255.).round() as u8;
+    let mut fill = format!(
+        " fill=\"rgb({},{},{})\"",
+        channel(color.red),
+        channel(color.green),
+        channel(color.blue)
+    );
+    if color.alpha < 1. {
+        write!(fill, " fill-opacity=\"{}\"", color.alpha.max(0.)).unwrap();
+    }
+    fill
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command::ClearCommand;
+    use solstice_2d::{Rectangle, RegularPolygon};
+
+    #[test]
+    fn svg() {
+        let commands = [
+            Command::Draw(DrawCommand::new(
+                Rectangle::new(0., 0., 1., 1.),
+                Transform3D::default(),
+                Color::new(1., 1., 1., 1.),
+                None,
+            )),
+            Command::Clear(ClearCommand::new(Color::new(0., 0., 0., 1.))),
+            Command::Draw(DrawCommand::new(
+                Rectangle::new(10., 20., -4., 2.),
+                Transform3D::translation(5., 6., 0.),
+                Color::new(1., 0., 0., 0.5),
+                None,
+            )),
+            Command::Draw(DrawCommand::new(
+                RegularPolygon::new(0., 0., 4, 2.),
+                Transform3D::scale(2., 3., 1.),
+                Color::new(0., 0.5, 1., 1.),
+                None,
+            )),
+        ];
+        let svg = to_svg(&commands, 100, 50);
+        let lines = svg.lines().collect::<Vec<_>>();
+        assert_eq!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"100\" height=\"50\" \
+             viewBox=\"0 0 100 50\">",
+            lines[0]
+        );
+        assert_eq!(
+            "  <rect width=\"100%\" height=\"100%\" fill=\"rgb(0,0,0)\"/>",
+            lines[1]
+        );
+        assert_eq!(
+            "  <rect x=\"6\" y=\"20\" width=\"4\" height=\"2\" transform=\"matrix(1 0 0 1 5 6)\" \
+             fill=\"rgb(255,0,0)\" fill-opacity=\"0.5\"/>",
+            lines[2]
+        );
+        assert!(lines[3].starts_with("  <polygon points=\"2,0 "));
+        assert!(lines[3].ends_with(" transform=\"matrix(2 0 0 3 0 0)\" fill=\"rgb(0,128,255)\"/>"));
+        assert_eq!("</svg>", lines[4]);
+        assert_eq!(5, lines.len());
+    }
+}
@@ -0,0 +1,461 @@
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+use std::path::Path;
+
+// Graphs are saved as `{"version": VERSION, "graph": ...}`. Anything saved before the version was
+// added is version 0.
+pub const VERSION: u32 = 1;
+
+// Rewrites a graph saved with one version so that it matches the next.
+pub type Migration = fn(&mut Value) -> Result<(), String>;
+
+// `MIGRATIONS[n]` upgrades version `n` to `n + 1`. When the saved form of the graph or of a node
+// changes bump `VERSION` and add the migration here so that older graphs keep loading.
+pub static MIGRATIONS: &[Migration] = &[
+    // only the envelope was added
+    |_graph| Ok(()),
+];
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Format {
+    Json,
+    // For editing graphs by hand. Needs the `ron` feature.
+    Ron,
+    // For embedding graphs in builds. Needs the `msgpack` feature.
+    MessagePack,
+}
+
+impl Format {
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            "json" => Some(Format::Json),
+            "ron" => Some(Format::Ron),
+            "msgpack" | "mpk" => Some(Format::MessagePack),
+            _ => None,
+        }
+    }
+
+    // Saved graphs always start with a map, which is enough to tell the formats apart.
+    pub fn detect(data: &[u8]) -> Self {
+        match data.iter().find(|byte| !byte.is_ascii_whitespace()) {
+            Some(b'{') => Format::Json,
+            Some(0x80..=0x8f) | Some(0xde) | Some(0xdf) => Format::MessagePack,
+            _ => Format::Ron,
+        }
+    }
+
+    fn encode<W, T>(self, writer: W, value: &T) -> Result<(), Error>
+    where
+        W: std::io::Write,
+        T: Serialize,
+    {
+        match self {
+            Format::Json => Ok(serde_json::to_writer_pretty(writer, value)?),
+            #[cfg(feature = "ron")]
+            Format::Ron => {
+                let config = ron::ser::PrettyConfig::default();
+                ron::ser::to_writer_pretty(writer, value, config).map_err(|err| self.error(err))
+            }
+            #[cfg(feature = "msgpack")]
+            Format::MessagePack => {
+                let mut writer = writer;
+                rmp_serde::encode::write_named(&mut writer, value).map_err(|err| self.error(err))
+            }
+            #[allow(unreachable_patterns)]
+            _ => Err(Error::Disabled(self)),
+        }
+    }
+
+    fn decode(self, data: &[u8]) -> Result<Value, Error> {
+        match self {
+            Format::Json => Ok(serde_json::from_slice(data)?),
+            #[cfg(feature = "ron")]
+            Format::Ron => {
+                // structs can only be told apart from tuples once they've been parsed
+                let value: ron::Value = ron::de::from_bytes(data).map_err(|err| self.error(err))?;
+                value.into_rust().map_err(|err| self.error(err))
+            }
+            #[cfg(feature = "msgpack")]
+            Format::MessagePack => rmp_serde::from_slice(data).map_err(|err| self.error(err)),
+            #[allow(unreachable_patterns)]
+            _ => Err(Error::Disabled(self)),
+        }
+    }
+
+    #[allow(dead_code)]
+    fn error<E: std::fmt::Display>(self, err: E) -> Error {
+        Error::Encoding {
+            format: self,
+            message: err.to_string(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum Error {
+    Io(std::io::Error),
+    Json(serde_json::Error),
+    Encoding { format: Format, message: String },
+    // The format's feature wasn't enabled.
+    Disabled(Format),
+    // The file was saved by a newer version than this one.
+    TooNew { version: u64 },
+    Migration { from: u32, message: String },
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Io(err) => write!(f, "{}", err),
+            Error::Json(err) => write!(f, "{}", err),
+            Error::Encoding { format, message } => write!(f, "{:?}: {}", format, message),
+            Error::Disabled(format) => write!(f, "support for {:?} wasn't enabled", format),
+            Error::TooNew { version } => write!(
+                f,
+                "graph was saved with format version {} but only versions up to {} are supported",
+                version, VERSION
+            ),
+            Error::Migration { from, message } => write!(
+                f,
+                "failed to upgrade graph from format version {}: {}",
+                from, message
+            ),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(err) => Some(err),
+            Error::Json(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(err: std::io::Error) -> Self {
+        Error::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(err: serde_json::Error) -> Self {
+        Error::Json(err)
+    }
+}
+
+#[derive(Serialize)]
+struct Versioned<'a, T> {
+    version: u32,
+    graph: &'a T,
+}
+
+// Works for a `UIGraph` as well as a bare `Graph`.
+pub fn save<W: std::io::Write, T: Serialize>(writer: W, graph: &T) -> Result<(), Error> {
+    save_as(writer, graph, Format::Json)
+}
+
+pub fn save_as<W, T>(writer: W, graph: &T, format: Format) -> Result<(), Error>
+where
+    W: std::io::Write,
+    T: Serialize,
+{
+    let versioned = Versioned {
+        version: VERSION,
+        graph,
+    };
+    format.encode(writer, &versioned)
+}
+
+// The format is picked from the extension, falling back to JSON.
+pub fn save_file<T: Serialize>(path: &Path, graph: &T) -> Result<(), Error> {
+    let format = Format::from_path(path).unwrap_or(Format::Json);
+    let writer = std::io::BufWriter::new(std::fs::File::create(path)?);
+    save_as(writer, graph, format)
+}
+
+// The format is detected from the content.
+pub fn load<T: DeserializeOwned>(data: &[u8]) -> Result<T, Error> {
+    load_as(data, Format::detect(data))
+}
+
+pub fn load_as<T: DeserializeOwned>(data: &[u8], format: Format) -> Result<T, Error> {
+    let value = format.decode(data)?;
+    let graph = upgrade(value, MIGRATIONS)?;
+    Ok(serde_json::from_value(graph)?)
+}
+
+// The format is picked from the extension, falling back to the content.
+pub fn load_file<T: DeserializeOwned>(path: &Path) -> Result<T, Error> {
+    let data = std::fs::read(path)?;
+    let format = Format::from_path(path).unwrap_or_else(|| Format::detect(&data));
+    load_as(&data, format)
+}
+
+// Returns the graph in the current format.
+fn upgrade(value: Value, migrations: &[Migration]) -> Result<Value, Error> {
+    let (version, mut graph) = match value {
+        Value::Object(mut map) if map.contains_key("version") => {
+            let version =
+                map.get("version")
+                    .and_then(Value::as_u64)
+                    .ok_or_else(|| Error::Migration {
+                        from: 0,
+                        message: "the format version isn't a number".into(),
+                    })?;
+            let graph = map.remove("graph").unwrap_or(Value::Null);
+            (version, graph)
+        }
+        value => (0, value),
+    };
+
+    let current = migrations.len() as u64;
+    if version > current {
+        return Err(Error::TooNew { version });
+    }
+    for (from, migration) in migrations.iter().enumerate().skip(version as usize) {
+        migration(&mut graph).map_err(|message| Error::Migration {
+            from: from as u32,
+            message,
+        })?;
+    }
+    Ok(graph)
+}
+
+// Visits the saved form of every node, including those inside of subgraphs. Nodes are saved as
+// `{"type": "TypeName", ..fields}`.
+pub fn for_each_node<F>(graph: &mut Value, f: &mut F) -> Result<(), String>
+where
+    F: FnMut(&mut serde_json::Map<String, Value>) -> Result<(), String>,
+{
+    // a UIGraph wraps the node graph
+    let graph = if graph.get("inner").is_some() {
+        &mut graph["inner"]
+    } else {
+        graph
+    };
+    let nodes = graph
+        .get_mut("nodes")
+        .and_then(slots)
+        .ok_or("graph is missing its nodes")?;
+    for node in nodes {
+        if let Some(node) = node.as_object_mut() {
+            f(node)?;
+            if node_type(node) == Some("SubgraphNode") {
+                if let Some(inner) = node.get_mut("graph") {
+                    for_each_node(inner, f)?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+// Renames a node type, for when a node struct or its typetag name changes.
+pub fn rename_node(graph: &mut Value, from: &str, to: &str) -> Result<(), String> {
+    for_each_node(graph, &mut |node| {
+        if node_type(node) == Some(from) {
+            node.insert("type".into(), to.into());
+        }
+        Ok(())
+    })
+}
+
+// Rewrites the fields of every node of the given type.
+pub fn map_node<F>(graph: &mut Value, name: &str, mut f: F) -> Result<(), String>
+where
+    F: FnMut(&mut serde_json::Map<String, Value>) -> Result<(), String>,
+{
+    for_each_node(graph, &mut |node| {
+        if node_type(node) == Some(name) {
+            f(node)
+        } else {
+            Ok(())
+        }
+    })
+}
+
+fn node_type(node: &serde_json::Map<String, Value>) -> Option<&str> {
+    node.get("type").and_then(Value::as_str)
+}
+
+// Rewrites every `Metadata`, including the saved layout of collapsed subgraphs.
+pub fn map_metadata<F>(graph: &mut Value, mut f: F) -> Result<(), String>
+where
+    F: FnMut(&mut serde_json::Map<String, Value>) -> Result<(), String>,
+{
+    let graph = graph.as_object_mut().ok_or("graph isn't an object")?;
+    let mut layouts = vec![];
+    for (key, value) in graph.iter_mut() {
+        match key.as_str() {
+            "metadata" => layouts.push(value),
+            "collapsed" => layouts.extend(slots(value).into_iter().flatten()),
+            _ => {}
+        }
+    }
+    for layout in layouts {
+        let metadata = slots(layout).ok_or("metadata isn't a slot map")?;
+        for metadata in metadata.filter_map(Value::as_object_mut) {
+            f(metadata)?;
+        }
+    }
+    Ok(())
+}
+
+// The values held by a serialized slotmap, which is a sequence of `{"value": ..}` slots where
+// the value of a vacant slot is null.
+fn slots(map: &mut Value) -> Option<impl Iterator<Item = &mut Value>> {
+    let slots = map.as_array_mut()?;
+    let values = slots.iter_mut().filter_map(|slot| slot.get_mut("value"));
+    Some(values.filter(|value| !value.is_null()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::UIGraph;
+    use ::nodes::{AddNode, ConstantNode, ExpressionNode, Graph, One, SubgraphNode};
+
+    fn graph() -> UIGraph {
+        let mut inner = Graph::with_root(AddNode);
+        let one = inner.add_node(ConstantNode::Unsigned(1));
+        inner.connect(one, inner.root(), 0).unwrap();
+        inner.connect(one, inner.root(), 1).unwrap();
+        let subgraph = SubgraphNode::new(inner, vec![]);
+
+        let mut graph = UIGraph::new(Default::default(), AddNode, 0., 0.);
+        let two = graph.add_node(ConstantNode::Unsigned(2), 0., 0.);
+        let subgraph = graph.add_node(subgraph, 0., 0.);
+        graph.connect(two, graph.root(), 0).unwrap();
+        graph.connect(subgraph, graph.root(), 1).unwrap();
+        graph.add_node(ConstantNode::Float(0.25), 100., 0.);
+        graph.add_node(ConstantNode::Text("(\"text\")".into()), 0., 100.);
+        graph.add_node(ExpressionNode::new("sin(t) * 2"), 100., 100.);
+        graph
+    }
+
+    fn round_trip_as(format: Format) {
+        let original = graph();
+        let mut data = vec![];
+        save_as(&mut data, &original, format).unwrap();
+        assert_eq!(format, Format::detect(&data));
+        let mut graph = load::<UIGraph>(&data).unwrap();
+        let output = graph.execute().unwrap();
+        assert_eq!(4, **output.downcast::<One<u32>>().unwrap());
+        let saved = |graph: &UIGraph| serde_json::to_value(graph).unwrap();
+        assert_eq!(saved(&original), saved(&graph));
+
+        let mut data = vec![];
+        save_as(&mut data, original.inner(), format).unwrap();
+        let mut inner = load::<Graph>(&data).unwrap();
+        let output = inner.execute().unwrap();
+        assert_eq!(4, **output.downcast::<One<u32>>().unwrap());
+    }
+
+    #[test]
+    fn round_trip() {
+        round_trip_as(Format::Json);
+
+        let mut data = vec![];
+        save(&mut data, &graph()).unwrap();
+        let value: Value = serde_json::from_slice(&data).unwrap();
+        assert_eq!(Some(VERSION as u64), value["version"].as_u64());
+        assert_eq!(VERSION as usize, MIGRATIONS.len());
+    }
+
+    #[cfg(feature = "ron")]
+    #[test]
+    fn ron() {
+        round_trip_as(Format::Ron);
+    }
+
+    #[cfg(feature = "msgpack")]
+    #[test]
+    fn msgpack() {
+        round_trip_as(Format::MessagePack);
+    }
+
+    #[test]
+    fn from_path() {
+        let format = |path: &str| Format::from_path(Path::new(path));
+        assert_eq!(Some(Format::Json), format("graph.json"));
+        assert_eq!(Some(Format::Ron), format("resources/graph.ron"));
+        assert_eq!(Some(Format::MessagePack), format("graph.mpk"));
+        assert_eq!(None, format("graph"));
+    }
+
+    #[test]
+    fn unversioned() {
+        let data = serde_json::to_vec(&graph()).unwrap();
+        let mut graph = load::<UIGraph>(&data).unwrap();
+        assert!(graph.execute().is_some());
+    }
+
+    #[test]
+    fn too_new() {
+        let data = serde_json::json!({ "version": VERSION + 1, "graph": graph() });
+        match load(&serde_json::to_vec(&data).unwrap()) {
+            Err(Error::TooNew { version }) => assert_eq!(u64::from(VERSION) + 1, version),
+            other => panic!("{:?}", other.map(|_: UIGraph| ())),
+        }
+        // too big for a u32 but still newer, not version 0
+        let data = serde_json::json!({ "version": 1u64 << 32, "graph": graph() });
+        match load(&serde_json::to_vec(&data).unwrap()) {
+            Err(Error::TooNew { version }) => assert_eq!(1 << 32, version),
+            other => panic!("{:?}", other.map(|_: UIGraph| ())),
+        }
+    }
+
+    #[test]
+    fn migrations() {
+        // pretend that an older version called it `Constant` and had unsigned integers as `Int`
+        let mut old = serde_json::to_value(graph()).unwrap();
+        rename_node(&mut old, "ConstantNode", "Constant").unwrap();
+        map_node(&mut old, "Constant", |fields| {
+            if let Some(value) = fields.remove("Unsigned") {
+                fields.insert("Int".into(), value);
+            }
+            Ok(())
+        })
+        .unwrap();
+        map_metadata(&mut old, |metadata| {
+            metadata.remove("dimensions");
+            Ok(())
+        })
+        .unwrap();
+        let old = serde_json::json!({ "version": 1, "graph": old });
+        assert!(serde_json::from_value::<UIGraph>(old["graph"].clone()).is_err());
+
+        let migrations: &[Migration] = &[
+            |_graph| Ok(()),
+            |graph| rename_node(graph, "Constant", "ConstantNode"),
+            |graph| {
+                map_node(graph, "ConstantNode", |fields| {
+                    if let Some(value) = fields.remove("Int") {
+                        fields.insert("Unsigned".into(), value);
+                    }
+                    Ok(())
+                })
+            },
+            |graph| {
+                map_metadata(graph, |metadata| {
+                    let dimensions = serde_json::json!({ "width": 100., "height": 100. });
+                    metadata.insert("dimensions".into(), dimensions);
+                    Ok(())
+                })
+            },
+        ];
+        let upgraded = upgrade(old, migrations).unwrap();
+        let mut graph = serde_json::from_value::<UIGraph>(upgraded).unwrap();
+        let output = graph.execute().unwrap();
+        assert_eq!(4, **output.downcast::<::nodes::One<u32>>().unwrap());
+
+        let broken = serde_json::json!({ "version": 2, "graph": {} });
+        match upgrade(broken, migrations) {
+            Err(Error::Migration { from: 2, .. }) => {}
+            other => panic!("{:?}", other),
+        }
+    }
+}
@@ -0,0 +1,227 @@
+use crate::command::{Command, SoftwareRenderer};
+use ::nodes::{EvalContext, Graph, One};
+use std::fmt::Write;
+
+// The commands a graph produced for one tick.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct Frame {
+    pub tick: u32,
+    pub commands: Vec<Command>,
+}
+
+#[derive(Debug)]
+pub enum Error {
+    Graph { tick: u32, error: ::nodes::Error },
+    // The root didn't output a command list, i.e. it isn't a screen node.
+    Output { tick: u32, found: &'static str },
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Graph { tick, error } => write!(f, "tick {}: {}", tick, error),
+            Error::Output { tick, found } => write!(
+                f,
+                "tick {}: expected the graph to output commands but found {}",
+                tick, found
+            ),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+// Executes the graph for a single frame.
+pub fn frame(graph: &mut Graph, ctx: EvalContext) -> Result<Frame, Error> {
+    let tick = ctx.frame;
+    let result = graph
+        .execute_at(ctx)
+        .map_err(|error| Error::Graph { tick, error })?;
+    match result.downcast::<One<Vec<Command>>>() {
+        Ok(commands) => Ok(Frame {
+            tick,
+            commands: commands.inner(),
+        }),
+        Err(result) => {
+            let found = result.type_name();
+            Err(Error::Output { tick, found })
+        }
+    }
+}
+
+// Executes the graph for every frame in the range, each one `ctx.delta` seconds after the last.
+pub fn run(
+    graph: &mut Graph,
+    frames: std::ops::Range<u32>,
+    ctx: EvalContext,
+) -> Result<Vec<Frame>, Error> {
+    frames.map(|tick| frame(graph, ctx.at(tick))).collect()
+}
+
+// Executes the graph from `ctx.frame` until it draws the same thing as it did on the first frame,
+// giving one period of the loop. `None` if that doesn't happen within `max` frames.
+pub fn run_loop(
+    graph: &mut Graph,
+    ctx: EvalContext,
+    max: usize,
+) -> Result<Option<Vec<Frame>>, Error> {
+    let first = frame(graph, ctx)?;
+    let first_commands = serde_json::to_value(&first.commands).unwrap();
+    let mut frames = vec![first];
+    while frames.len() < max {
+        let frame = frame(graph, ctx.at(ctx.frame + frames.len() as u32))?;
+        if serde_json::to_value(&frame.commands).unwrap() == first_commands {
+            return Ok(Some(frames));
+        }
+        frames.push(frame);
+    }
+    Ok(None)
+}
+
+// One line per command under a header for each frame.
+pub fn to_text(frames: &[Frame]) -> String {
+    let mut text = String::new();
+    for frame in frames {
+        writeln!(text, "tick {}", frame.tick).unwrap();
+        for command in frame.commands.iter() {
+            writeln!(text, "    {:?}", command).unwrap();
+        }
+    }
+    text
+}
+
+pub fn to_json(frames: &[Frame]) -> serde_json::Value {
+    serde_json::to_value(frames).unwrap()
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct GifOptions {
+    pub width: u16,
+    pub height: u16,
+    // In hundredths of a second.
+    pub delay: u16,
+    // How hard to work on each frame's palette, from 1 (best) to 30 (fastest).
+    pub speed: i32,
+}
+
+impl Default for GifOptions {
+    fn default() -> Self {
+        Self {
+            width: 640,
+            height: 360,
+            delay: 2,
+            speed: 10,
+        }
+    }
+}
+
+// Draws each frame in software and encodes them as a GIF that loops forever.
+pub fn write_gif<W: std::io::Write>(
+    frames: &[Frame],
+    options: GifOptions,
+    writer: W,
+) -> Result<(), gif::EncodingError> {
+    let GifOptions {
+        width,
+        height,
+        delay,
+        speed,
+    } = options;
+    let mut encoder = gif::Encoder::new(writer, width, height, &[])?;
+    encoder.set_repeat(gif::Repeat::Infinite)?;
+    for frame in frames {
+        let mut renderer = SoftwareRenderer::new(width.into(), height.into());
+        renderer.render(&frame.commands);
+        let mut pixels = renderer.pixels().to_vec();
+        let mut frame = gif::Frame::from_rgba_speed(width, height, &mut pixels, speed.clamp(1, 30));
+        frame.delay = delay;
+        encoder.write_frame(&frame)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ClearNode, ColorNode, ScreenNode};
+    use ::nodes::{ConstantNode, GlobalNode, RangeNode, RatioNode};
+
+    #[test]
+    fn frames() {
+        let mut graph = Graph::with_root(ScreenNode);
+        let clear = graph.add_node(ClearNode);
+        let color = graph.add_node(ColorNode);
+        let one = graph.add_node(ConstantNode::Float(1.));
+        for input in 0..4 {
+            graph.connect(one, color, input).unwrap();
+        }
+        graph.connect(color, clear, 0).unwrap();
+        graph.connect(clear, graph.root(), 0).unwrap();
+
+        let frames = run(&mut graph, 1..3, EvalContext::default()).unwrap();
+        assert_eq!(2, frames.len());
+        assert_eq!(frames[0].tick + 1, frames[1].tick);
+        assert!(frames.iter().all(|frame| frame.commands.len() == 1));
+
+        let text = to_text(&frames);
+        assert_eq!(4, text.lines().count());
+        assert!(text.lines().nth(1).unwrap().starts_with("    Clear("));
+        let json = to_json(&frames);
+        assert_eq!(frames[1].tick, json[1]["tick"]);
+        assert_eq!(1., json[0]["commands"][0]["Clear"]["color"]["alpha"]);
+        let loaded: Vec<Frame> = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(json, to_json(&loaded));
+
+        let mut graph = Graph::with_root(RangeNode);
+        let error = frame(&mut graph, EvalContext::default()).unwrap_err();
+        assert!(matches!(error, Error::Graph { .. }));
+        let mut graph = Graph::with_root(ConstantNode::Unsigned(1));
+        let error = frame(&mut graph, EvalContext::default()).unwrap_err();
+        assert!(matches!(
+            error,
+            Error::Output {
+                found: "nodes::One<u32>",
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn animation() {
+        // the red channel goes around every 4 ticks
+        let mut graph = Graph::with_root(ScreenNode);
+        let clear = graph.add_node(ClearNode);
+        let color = graph.add_node(ColorNode);
+        let ratio = graph.add_node(RatioNode);
+        let global = graph.add_node(GlobalNode);
+        let period = graph.add_node(ConstantNode::Unsigned(4));
+        let one = graph.add_node(ConstantNode::Float(1.));
+        graph.connect(global, ratio, 0).unwrap();
+        graph.connect(period, ratio, 1).unwrap();
+        graph.connect(ratio, color, 0).unwrap();
+        for input in 1..4 {
+            graph.connect(one, color, input).unwrap();
+        }
+        graph.connect(color, clear, 0).unwrap();
+        graph.connect(clear, graph.root(), 0).unwrap();
+
+        let ctx = EvalContext::default();
+        let frames = run(&mut graph, 10..13, ctx).unwrap();
+        let ticks = frames.iter().map(|frame| frame.tick).collect::<Vec<_>>();
+        assert_eq!(vec![10, 11, 12], ticks);
+
+        let frames = run_loop(&mut graph, ctx.at(3), 100).unwrap().unwrap();
+        let ticks = frames.iter().map(|frame| frame.tick).collect::<Vec<_>>();
+        assert_eq!(vec![3, 4, 5, 6], ticks);
+        assert!(run_loop(&mut graph, ctx.at(3), 3).unwrap().is_none());
+
+        let options = GifOptions {
+            width: 8,
+            height: 4,
+            ..Default::default()
+        };
+        let mut gif = Vec::new();
+        write_gif(&frames, options, &mut gif).unwrap();
+        assert_eq!(b"GIF89a", &gif[..6]);
+    }
+}
@@ -0,0 +1,296 @@
+use crate::{Metadata, UIGraph};
+use nodes::{Connection, ConnectionError, Node, NodeID};
+
+// Everything needed to put a removed node back the way it was.
+#[derive(Debug, Clone)]
+pub(crate) struct Removed {
+    pub id: NodeID,
+    pub node: Box<dyn Node>,
+    pub metadata: Metadata,
+    pub layout: Option<slotmap::SecondaryMap<NodeID, Metadata>>,
+    pub connections: Vec<Connection>,
+}
+
+// A reversible change to a UIGraph. Applying one returns the edit that undoes it.
+#[derive(Debug, Clone)]
+pub(crate) enum Edit {
+    Insert(Box<Removed>),
+    Remove(NodeID),
+    Connect(Connection),
+    Disconnect { to: NodeID, input: usize },
+    Metadata(NodeID, Metadata),
+    Replace(NodeID, Box<dyn Node>),
+    Batch(Vec<Edit>),
+}
+
+impl Edit {
+    // Whether this touches nodes or connections, as opposed to only where things are drawn.
+    fn changes_graph(&self) -> bool {
+        match self {
+            Edit::Metadata(..) => false,
+            Edit::Batch(edits) => edits.iter().any(Edit::changes_graph),
+            _ => true,
+        }
+    }
+
+    // Re-inserting a node gives it a new id so anything still referring to the old one is updated.
+    fn remap(&mut self, from: NodeID, to: NodeID) {
+        let swap = |id: &mut NodeID| {
+            if *id == from {
+                *id = to;
+            }
+        };
+        match self {
+            Edit::Insert(removed) => {
+                swap(&mut removed.id);
+                for connection in removed.connections.iter_mut() {
+                    swap(&mut connection.from);
+                    swap(&mut connection.to);
+                }
+            }
+            Edit::Remove(id) | Edit::Metadata(id, _) | Edit::Replace(id, _) => swap(id),
+            Edit::Connect(connection) => {
+                swap(&mut connection.from);
+                swap(&mut connection.to);
+            }
+            Edit::Disconnect { to, .. } => swap(to),
+            Edit::Batch(edits) => {
+                for edit in edits.iter_mut() {
+                    edit.remap(from, to);
+                }
+            }
+        }
+    }
+}
+
+// Consecutive edits with the same key are merged into a single history entry.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Coalesce {
+    Move(Vec<NodeID>),
+    Resize(NodeID),
+    Replace(NodeID),
+}
+
+#[derive(Debug, Clone)]
+struct Entry {
+    edit: Edit,
+    key: Option<Coalesce>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub(crate) struct History {
+    undo: Vec<Entry>,
+    redo: Vec<Entry>,
+    group: Option<Vec<Edit>>,
+    sealed: bool,
+}
+
+impl History {
+    fn push(&mut self, inverse: Edit, key: Option<Coalesce>) {
+        if let Some(group) = self.group.as_mut() {
+            group.push(inverse);
+            return;
+        }
+
+        self.redo.clear();
+        let coalesce =
+            key.is_some() && !self.sealed && self.undo.last().map(|entry| &entry.key) == Some(&key);
+        // the existing entry already restores the state from before the first of these edits
+        if !coalesce {
+            self.undo.push(Entry { edit: inverse, key });
+        }
+        self.sealed = false;
+    }
+
+    fn remap(&mut self, from: NodeID, to: NodeID) {
+        let entries = self.undo.iter_mut().chain(self.redo.iter_mut());
+        for entry in entries {
+            entry.edit.remap(from, to);
+        }
+        for edit in self.group.iter_mut().flatten() {
+            edit.remap(from, to);
+        }
+    }
+}
+
+impl UIGraph {
+    pub fn undo(&mut self) -> bool {
+        match self.history.undo.pop() {
+            Some(entry) => {
+                let edit = self.replay(entry.edit);
+                self.history.redo.push(Entry { edit, key: None });
+                self.history.sealed = true;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn redo(&mut self) -> bool {
+        match self.history.redo.pop() {
+            Some(entry) => {
+                let edit = self.replay(entry.edit);
+                self.history.undo.push(Entry { edit, key: None });
+                self.history.sealed = true;
+                true
+            }
+            None => false,
+        }
+    }
+
+    // Stops the next edit from being merged into the previous history entry.
+    pub fn checkpoint(&mut self) {
+        self.history.sealed = true;
+    }
+
+    // Forgets every edit so far, for once a graph has been built or loaded.
+    pub fn clear_history(&mut self) {
+        self.history = History::default();
+    }
+
+    // Records every edit made by `f` as a single history entry. If `f` fails the edits it made
+    // are rolled back.
+    pub fn group<R, F>(&mut self, f: F) -> Option<R>
+    where
+        F: FnOnce(&mut Self) -> Option<R>,
+    {
+        let outer = self.history.group.replace(vec![]);
+        let result = f(self);
+        let mut edits = std::mem::replace(&mut self.history.group, outer).unwrap_or_default();
+        edits.reverse();
+        let inverse = Edit::Batch(edits);
+        if result.is_some() {
+            self.record(inverse, None);
+        } else {
+            self.replay(inverse);
+        }
+        result
+    }
+
+    pub(crate) fn record(&mut self, inverse: Edit, key: Option<Coalesce>) {
+        // dragging nodes around can't change any types, and a group infers once when it's done
+        if self.history.group.is_none() && inverse.changes_graph() {
+            self.infer_types();
+        }
+        if let Edit::Batch(edits) = &inverse {
+            if edits.is_empty() {
+                return;
+            }
+        }
+        self.history.push(inverse, key);
+    }
+
+    fn replay(&mut self, edit: Edit) -> Edit {
+        let mut remaps = vec![];
+        let changes_graph = edit.changes_graph();
+        let mut inverse = self.apply(edit, &mut remaps);
+        if changes_graph {
+            self.infer_types();
+        }
+        for (from, to) in remaps {
+            inverse.remap(from, to);
+            self.history.remap(from, to);
+        }
+        inverse
+    }
+
+    fn apply(&mut self, edit: Edit, remaps: &mut Vec<(NodeID, NodeID)>) -> Edit {
+        match edit {
+            Edit::Insert(removed) => {
+                let previous = removed.id;
+                let id = self.restore_node(*removed);
+                remaps.push((previous, id));
+                Edit::Remove(id)
+            }
+            Edit::Remove(id) => match self.take_node(id) {
+                Some(removed) => Edit::Insert(Box::new(removed)),
+                None => Edit::Batch(vec![]),
+            },
+            Edit::Connect(c) => self
+                .set_connection(c.from, c.output, c.to, c.input)
+                .unwrap_or(Edit::Batch(vec![])),
+            Edit::Disconnect { to, input } => match self.inner.disconnect(to, input) {
+                Some(connection) => Edit::Connect(connection),
+                None => Edit::Batch(vec![]),
+            },
+            Edit::Metadata(id, metadata) => match self.metadata.get_mut(id) {
+                Some(slot) => Edit::Metadata(id, std::mem::replace(slot, metadata)),
+                None => Edit::Batch(vec![]),
+            },
+            Edit::Replace(id, node) => match self.inner.replace_node(id, node) {
+                Some(previous) => Edit::Replace(id, previous),
+                None => Edit::Batch(vec![]),
+            },
+            Edit::Batch(edits) => {
+                let mut inverse = Vec::with_capacity(edits.len());
+                for mut edit in edits {
+                    for (from, to) in remaps.iter().copied() {
+                        edit.remap(from, to);
+                    }
+                    inverse.push(self.apply(edit, remaps));
+                }
+                inverse.reverse();
+                Edit::Batch(inverse)
+            }
+        }
+    }
+
+    pub(crate) fn take_node(&mut self, id: NodeID) -> Option<Removed> {
+        let metadata = *self.metadata.get(id)?;
+        let connections = self
+            .inner
+            .connections()
+            .iter()
+            .filter(|c| c.connects(id))
+            .copied()
+            .collect();
+        let node = self.inner.remove_node(id)?;
+        self.metadata.remove(id);
+        Some(Removed {
+            id,
+            node,
+            metadata,
+            layout: self.collapsed.remove(id),
+            connections,
+        })
+    }
+
+    fn restore_node(&mut self, removed: Removed) -> NodeID {
+        let id = self.inner.add_boxed_node(removed.node);
+        self.metadata.insert(id, removed.metadata);
+        if let Some(layout) = removed.layout {
+            self.collapsed.insert(id, layout);
+        }
+        let previous = removed.id;
+        let swap = |node: NodeID| if node == previous { id } else { node };
+        for c in removed.connections {
+            let (from, to) = (swap(c.from), swap(c.to));
+            let nodes = self.inner.nodes();
+            if nodes.contains_key(from) && nodes.contains_key(to) {
+                self.inner.connect_output(from, c.output, to, c.input).ok();
+            }
+        }
+        id
+    }
+
+    // Returns the edit that puts back whatever was connected to the input before.
+    pub(crate) fn set_connection(
+        &mut self,
+        from: NodeID,
+        output: usize,
+        to: NodeID,
+        input: usize,
+    ) -> Result<Edit, ConnectionError> {
+        let previous = self
+            .inner
+            .connections()
+            .iter()
+            .find(|c| c.to == to && c.input == input)
+            .copied();
+        self.inner.connect_output(from, output, to, input)?;
+        Ok(match previous {
+            Some(connection) => Edit::Connect(connection),
+            None => Edit::Disconnect { to, input },
+        })
+    }
+}
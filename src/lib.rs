@@ -1,10 +1,13 @@
 pub mod command;
+pub mod file;
+pub mod headless;
+mod history;
 mod nodes;
 
 pub use self::nodes::*;
-use ::nodes::{ConnectionState, Graph, NodeID};
+use ::nodes::{AnyClone, Connection, ConnectionState, Graph, NodeID, NodeStatus};
+use history::{Coalesce, Edit, History};
 use solstice_2d::{Color, Draw, FontId, LineVertex, Rectangle};
-use std::any::Any;
 
 #[derive(Debug, Copy, Clone, serde::Serialize, serde::Deserialize)]
 pub struct Position {
@@ -75,12 +78,16 @@ impl Metadata {
         }
     }
 
-    pub fn output(&self) -> Rectangle {
+    // The output strip is shared evenly between all of a node's outputs.
+    pub fn output(&self, index: usize, count: usize) -> Rectangle {
+        let count = count.max(1);
+        let height =
+            (self.dimensions.height - Self::TOP_BAR_HEIGHT - Self::OUTPUT_WIDTH) / count as f32;
         Rectangle {
             x: self.position.x + self.dimensions.width - Self::OUTPUT_WIDTH,
-            y: self.position.y + Self::TOP_BAR_HEIGHT,
+            y: self.position.y + Self::TOP_BAR_HEIGHT + height * index as f32,
             width: Self::OUTPUT_WIDTH,
-            height: self.dimensions.height - Self::TOP_BAR_HEIGHT - Self::OUTPUT_WIDTH,
+            height,
         }
     }
 
@@ -111,8 +118,18 @@ impl Into<Rectangle> for &Metadata {
 pub struct UIGraph {
     inner: Graph,
     metadata: slotmap::SecondaryMap<NodeID, Metadata>,
+    // layout of the nodes inside of collapsed subgraphs so that expanding them restores it
+    #[serde(default)]
+    collapsed: slotmap::SecondaryMap<NodeID, slotmap::SecondaryMap<NodeID, Metadata>>,
     #[serde(skip)]
     font: FontId,
+    #[serde(skip)]
+    history: History,
+    #[serde(skip)]
+    inferred: Vec<ConnectionState>,
+    // how each node fared the last time the graph was executed
+    #[serde(skip)]
+    status: slotmap::SecondaryMap<NodeID, NodeStatus>,
 }
 
 impl UIGraph {
@@ -135,28 +152,75 @@ impl UIGraph {
         Self {
             inner,
             metadata,
+            collapsed: Default::default(),
             font,
+            history: Default::default(),
+            inferred: vec![],
+            status: Default::default(),
         }
     }
 
-    pub fn execute(&mut self) -> Result<Box<dyn Any>, ::nodes::Error> {
-        self.inner.execute()
+    pub fn set_context(&mut self, context: ::nodes::EvalContext) {
+        self.inner.set_context(context);
+    }
+
+    // Evaluates as much of the graph as possible so that every failing node can be shown.
+    pub fn execute(&mut self) -> Option<Box<dyn AnyClone>> {
+        let evaluation = self.inner.execute_best_effort();
+        self.status = evaluation.status;
+        evaluation.output
+    }
+
+    pub fn status(&self) -> &slotmap::SecondaryMap<NodeID, NodeStatus> {
+        &self.status
     }
 
     pub fn inner(&self) -> &Graph {
         &self.inner
     }
 
+    // Statically checks every connection. Edits do this automatically.
+    pub fn infer_types(&mut self) {
+        self.inferred = self.inner.infer_types();
+    }
+
+    pub fn inferred_types(&self) -> &[ConnectionState] {
+        &self.inferred
+    }
+
+    // Graphviz source with nodes pinned to where they are in the editor, for use with `neato -n`.
+    pub fn to_dot(&self) -> String {
+        self.inner.to_dot_with(|id| {
+            let rect: Rectangle = self.metadata.get(id)?.into();
+            let Position { x, y } = rect_center(&rect);
+            // graphviz's y axis points up
+            Some(format!("pos=\"{},{}!\"", x, -y))
+        })
+    }
+
+    // Changes made through this aren't recorded in the edit history.
     pub fn node_mut(&mut self, id: NodeID) -> Option<&mut dyn ::nodes::Node> {
         self.inner.node_mut(id)
     }
 
+    pub fn replace_node(
+        &mut self,
+        id: NodeID,
+        node: Box<dyn ::nodes::Node>,
+    ) -> Option<Box<dyn ::nodes::Node>> {
+        let previous = self.inner.replace_node(id, node)?;
+        self.record(
+            Edit::Replace(id, previous.clone()),
+            Some(Coalesce::Replace(id)),
+        );
+        Some(previous)
+    }
+
     pub fn remove_node(&mut self, id: NodeID) -> Option<Box<dyn ::nodes::Node>> {
-        let removed = self.inner.remove_node(id);
-        if removed.is_some() {
-            self.metadata.remove(id);
-        }
-        removed
+        let removed = self.take_node(id)?;
+        let node = removed.node.clone();
+        self.record(Edit::Insert(Box::new(removed)), None);
+        Some(node)
     }
 
     pub fn root(&self) -> NodeID {
@@ -167,10 +231,32 @@ impl UIGraph {
         &self.metadata
     }
 
+    // Changes made through this aren't recorded in the edit history.
     pub fn metadata_mut(&mut self, node: NodeID) -> Option<&mut Metadata> {
         self.metadata.get_mut(node)
     }
 
+    pub fn move_nodes(&mut self, nodes: &[NodeID], dx: f32, dy: f32) {
+        let mut inverse = vec![];
+        for id in nodes.iter().copied() {
+            if let Some(metadata) = self.metadata.get_mut(id) {
+                inverse.push(Edit::Metadata(id, *metadata));
+                metadata.position.x += dx;
+                metadata.position.y += dy;
+            }
+        }
+        self.record(Edit::Batch(inverse), Some(Coalesce::Move(nodes.to_vec())));
+    }
+
+    pub fn resize_node(&mut self, id: NodeID, dw: f32, dh: f32) {
+        if let Some(metadata) = self.metadata.get_mut(id) {
+            let inverse = Edit::Metadata(id, *metadata);
+            metadata.dimensions.width += dw;
+            metadata.dimensions.height += dh;
+            self.record(inverse, Some(Coalesce::Resize(id)));
+        }
+    }
+
     pub fn add_node<N>(&mut self, node: N, x: f32, y: f32) -> NodeID
     where
         N: ::nodes::Node + 'static,
@@ -190,11 +276,198 @@ impl UIGraph {
                 },
             },
         );
+        self.record(Edit::Remove(id), None);
         id
     }
 
-    pub fn connect(&mut self, from: NodeID, to: NodeID, input: usize) {
-        self.inner.connect(from, to, input)
+    pub fn connect(
+        &mut self,
+        from: NodeID,
+        to: NodeID,
+        input: usize,
+    ) -> Result<(), ::nodes::ConnectionError> {
+        self.connect_output(from, 0, to, input)
+    }
+
+    pub fn connect_output(
+        &mut self,
+        from: NodeID,
+        output: usize,
+        to: NodeID,
+        input: usize,
+    ) -> Result<(), ::nodes::ConnectionError> {
+        let inverse = self.set_connection(from, output, to, input)?;
+        self.record(inverse, None);
+        Ok(())
+    }
+
+    // Replaces the selected nodes with a single subgraph node. Everything leaving the selection
+    // has to come from one node since that becomes the subgraph's output.
+    pub fn collapse(&mut self, selected: &[NodeID]) -> Option<NodeID> {
+        let mut selected = selected
+            .iter()
+            .copied()
+            .filter(|id| self.inner.nodes().contains_key(*id))
+            .collect::<Vec<_>>();
+        selected.sort();
+        selected.dedup();
+        if selected.is_empty() || selected.contains(&self.root()) {
+            return None;
+        }
+
+        let inside = |id: NodeID| selected.contains(&id);
+        let connections = self.inner.connections().to_vec();
+        let outgoing = connections
+            .iter()
+            .filter(|c| inside(c.from) && !inside(c.to))
+            .copied()
+            .collect::<Vec<_>>();
+        let inner_root = match outgoing.first() {
+            Some(connection) => connection.from,
+            None => selected.iter().copied().find(|id| {
+                !connections
+                    .iter()
+                    .any(|c| c.from == *id && inside(c.to) && c.to != *id)
+            })?,
+        };
+        if outgoing.iter().any(|c| c.from != inner_root) {
+            return None;
+        }
+
+        let mut ids = slotmap::SecondaryMap::new();
+        let mut layout = slotmap::SecondaryMap::new();
+        let root = self.inner.nodes().get(inner_root)?.clone();
+        let mut inner = Graph::with_boxed_root(root);
+        ids.insert(inner_root, inner.root());
+        for id in selected.iter().copied() {
+            if id != inner_root {
+                let node = self.inner.nodes().get(id)?.clone();
+                ids.insert(id, inner.add_boxed_node(node));
+            }
+            if let Some(metadata) = self.metadata.get(id) {
+                layout.insert(ids[id], *metadata);
+            }
+        }
+
+        let mut exposed: Vec<::nodes::ExposedInput> = vec![];
+        let mut incoming = vec![];
+        for c in connections.iter() {
+            match (inside(c.from), inside(c.to)) {
+                (true, true) => inner
+                    .connect_output(ids[c.from], c.output, ids[c.to], c.input)
+                    .ok()?,
+                (false, true) => {
+                    let (node, input) = (ids[c.to], c.input);
+                    let index = match exposed
+                        .iter()
+                        .position(|e| e.node == node && e.input == input)
+                    {
+                        Some(index) => index,
+                        None => {
+                            let name =
+                                self.inner
+                                    .nodes()
+                                    .get(c.to)
+                                    .and_then(|n| {
+                                        n.inputs().groups.iter().find_map(|g| {
+                                            g.info.get(input).map(|i| i.name.to_string())
+                                        })
+                                    })
+                                    .unwrap_or_else(|| format!("input {}", input));
+                            exposed.push(::nodes::ExposedInput { node, input, name });
+                            exposed.len() - 1
+                        }
+                    };
+                    incoming.push((*c, index));
+                }
+                _ => {}
+            }
+        }
+
+        let count = selected.len() as f32;
+        let (x, y) = selected
+            .iter()
+            .filter_map(|id| self.metadata.get(*id))
+            .fold((0., 0.), |(x, y), m| {
+                (x + m.position.x / count, y + m.position.y / count)
+            });
+        // a collapse that would introduce a cycle is rolled back by the group
+        self.group(|graph| {
+            for id in selected.iter().copied() {
+                graph.remove_node(id);
+            }
+            let subgraph = graph.add_node(::nodes::SubgraphNode::new(inner, exposed), x, y);
+            graph.collapsed.insert(subgraph, layout);
+            for (c, index) in incoming {
+                graph
+                    .connect_output(c.from, c.output, subgraph, index)
+                    .ok()?;
+            }
+            for c in outgoing {
+                graph
+                    .connect_output(subgraph, c.output, c.to, c.input)
+                    .ok()?;
+            }
+            Some(subgraph)
+        })
+    }
+
+    // Replaces a subgraph node with the nodes inside of it, returning their new ids.
+    pub fn expand(&mut self, id: NodeID) -> Option<Vec<NodeID>> {
+        self.group(|graph| graph.expand_inner(id))
+    }
+
+    fn expand_inner(&mut self, id: NodeID) -> Option<Vec<NodeID>> {
+        let subgraph = self
+            .inner
+            .nodes()
+            .get(id)?
+            .downcast_ref::<::nodes::SubgraphNode>()?
+            .clone();
+        let (inner, exposed) = subgraph.into_inner();
+        let connections: Vec<Connection> = self
+            .inner
+            .connections()
+            .iter()
+            .filter(|c| c.connects(id))
+            .copied()
+            .collect();
+        let origin = self.metadata.get(id).copied()?;
+        let layout = self.collapsed.get(id).cloned().unwrap_or_default();
+        // the root can't be removed so it can't be expanded either
+        self.remove_node(id)?;
+
+        let mut ids = slotmap::SecondaryMap::new();
+        for (index, (inner_id, node)) in inner.nodes().iter().enumerate() {
+            let metadata = layout.get(inner_id).copied().unwrap_or(Metadata {
+                position: Position {
+                    x: origin.position.x + index as f32 * 120.,
+                    y: origin.position.y,
+                },
+                ..origin
+            });
+            let outer_id = self.add_boxed_node(node.clone(), 0., 0.);
+            self.metadata.insert(outer_id, metadata);
+            ids.insert(inner_id, outer_id);
+        }
+
+        for c in inner.connections() {
+            self.connect_output(ids[c.from], c.output, ids[c.to], c.input)
+                .ok()?;
+        }
+        for c in connections {
+            if c.to == id {
+                if let Some(e) = exposed.get(c.input) {
+                    self.connect_output(c.from, c.output, ids[e.node], e.input)
+                        .ok()?;
+                }
+            } else {
+                self.connect_output(ids[inner.root()], c.output, c.to, c.input)
+                    .ok()?;
+            }
+        }
+
+        Some(ids.values().copied().collect())
     }
 
     pub fn render(&self, g: &mut solstice_2d::GraphicsLock) {
@@ -205,7 +478,12 @@ impl UIGraph {
                 g.draw_with_color(background, Color::new(1., 0., 0., 1.));
                 g.stroke_with_color(background, black);
 
-                g.draw_with_color(metadata.top_bar(), Color::new(0.3, 0.3, 0.3, 1.));
+                let top_bar = match self.status.get(id) {
+                    Some(NodeStatus::Failed(_)) => Color::new(0.6, 0., 0., 1.),
+                    Some(NodeStatus::Skipped) => Color::new(0.15, 0.15, 0.15, 1.),
+                    _ => Color::new(0.3, 0.3, 0.3, 1.),
+                };
+                g.draw_with_color(metadata.top_bar(), top_bar);
                 g.stroke_with_color(metadata.top_bar(), black);
                 let text_bounds = Rectangle {
                     x: background.x + 5.,
@@ -213,8 +491,21 @@ impl UIGraph {
                 };
                 g.print(node.name(), self.font, 16., text_bounds);
 
-                g.draw_with_color(metadata.output(), Color::new(1., 1., 0., 1.));
-                g.stroke_with_color(metadata.output(), black);
+                if let Some(NodeStatus::Failed(error)) = self.status.get(id) {
+                    let bounds = Rectangle {
+                        y: background.y + background.height + 5.,
+                        height: 50.,
+                        ..background
+                    };
+                    g.print(error.to_string(), self.font, 16., bounds);
+                }
+
+                let outputs = node.outputs();
+                for index in 0..outputs.len() {
+                    let rect = metadata.output(index, outputs.len());
+                    g.draw_with_color(rect, Color::new(1., 1., 0., 1.));
+                    g.stroke_with_color(rect, black);
+                }
 
                 g.draw_with_color(metadata.resize(), Color::new(1., 0., 1., 1.));
                 g.stroke_with_color(metadata.resize(), black);
@@ -282,10 +573,25 @@ impl UIGraph {
                     }
                 }
 
+                {
+                    use ::nodes::ExpressionNode;
+                    if let Some(expression) = node.downcast_ref::<ExpressionNode>() {
+                        let bounds = Rectangle {
+                            x: metadata.position.x + 5.,
+                            y: metadata.position.y + Metadata::TOP_BAR_HEIGHT * 2.,
+                            width: metadata.dimensions.width - Metadata::OUTPUT_WIDTH,
+                            height: metadata.dimensions.height
+                                - 10.
+                                - Metadata::TOP_BAR_HEIGHT * 2.,
+                        };
+                        g.print(expression.expression().to_owned(), self.font, 16., bounds);
+                    }
+                }
+
                 {
                     use ::nodes::GlobalNode;
                     if node.is::<GlobalNode>() {
-                        let text = GlobalNode::load().to_string();
+                        let text = self.inner.context().frame.to_string();
                         let bounds = Rectangle {
                             x: metadata.position.x + 5.,
                             y: metadata.position.y + Metadata::TOP_BAR_HEIGHT * 2.,
@@ -300,11 +606,13 @@ impl UIGraph {
             }
         }
 
-        for connection in self.inner.connections() {
+        for (index, connection) in self.inner.connections().iter().enumerate() {
             let to = self.metadata.get(connection.to);
             let from = self.metadata.get(connection.from);
-            if let (Some(from), Some(to)) = (from, to) {
-                let from_pos = rect_center(&from.output());
+            let from_node = self.inner.nodes().get(connection.from);
+            if let (Some(from), Some(to), Some(from_node)) = (from, to, from_node) {
+                let outputs = from_node.outputs().len();
+                let from_pos = rect_center(&from.output(connection.output, outputs));
                 let to_pos = {
                     let rect = to.input(connection.input);
                     Position {
@@ -313,9 +621,19 @@ impl UIGraph {
                     }
                 };
 
-                let color = match connection.state {
+                // fall back to what type inference says about connections that weren't executed
+                let state = match connection.state {
+                    ConnectionState::Unevaluated => self
+                        .inferred
+                        .get(index)
+                        .copied()
+                        .unwrap_or(ConnectionState::Unevaluated),
+                    state => state,
+                };
+                let color = match state {
                     ConnectionState::Valid => [0., 1., 0., 1.],
                     ConnectionState::Invalid => [1., 0., 0., 1.],
+                    ConnectionState::Ambiguous => [1., 0.5, 0., 1.],
                     ConnectionState::Unevaluated => [1., 1., 1., 1.],
                 };
 
@@ -332,4 +650,153 @@ impl UIGraph {
 }
 
 #[cfg(test)]
-mod tests {}
+mod tests {
+    use super::*;
+    use ::nodes::{AddNode, ConstantNode, MultiplyNode, One};
+
+    #[test]
+    fn registry() {
+        let registry = crate::registry();
+        for entry in registry.entries() {
+            let doc = entry.doc();
+            for input in doc.inputs.iter() {
+                assert!(!input.description.is_empty(), "{}.{}", doc.name, input.name);
+            }
+        }
+        for name in ["screen", "translation", "rotation", "scaling", "shader"].iter() {
+            assert!(registry.get(name).is_some(), "{}", name);
+        }
+        let multiply = registry.get("multiply").unwrap().create();
+        assert!(multiply.downcast_ref::<ExtendedMultiplyNode>().is_some());
+    }
+
+    #[test]
+    fn to_dot() {
+        let mut graph = UIGraph::new(FontId::default(), AddNode, 0., 0.);
+        let one = graph.add_node(ConstantNode::Unsigned(1), 200., 100.);
+        graph.connect(one, graph.root(), 0).unwrap();
+        let dot = graph.to_dot();
+        let line = dot.lines().find(|line| line.contains("constant")).unwrap();
+        assert!(line.ends_with(r#"pos="250,-150!"];"#), "{}", line);
+        assert_eq!(1, dot.lines().filter(|line| line.contains("->")).count());
+    }
+
+    #[test]
+    fn collapse_and_expand() {
+        let mut graph = UIGraph::new(FontId::default(), AddNode, 0., 0.);
+        let root = graph.root();
+        let a = graph.add_node(ConstantNode::Unsigned(2), 0., 0.);
+        let b = graph.add_node(ConstantNode::Unsigned(3), 0., 0.);
+        let mul = graph.add_node(MultiplyNode, 0., 0.);
+        graph.connect(a, mul, 0).unwrap();
+        graph.connect(b, mul, 1).unwrap();
+        graph.connect(mul, root, 0).unwrap();
+        graph.connect(a, root, 1).unwrap();
+
+        let check = |graph: &mut UIGraph| {
+            let output = graph.execute().unwrap();
+            assert_eq!(output.downcast_ref::<One<u32>>(), Some(&One::new(8)));
+        };
+        check(&mut graph);
+
+        assert_eq!(graph.collapse(&[root]), None);
+        // both nodes feed things outside of the selection
+        assert_eq!(graph.collapse(&[a, mul]), None);
+
+        let subgraph = graph.collapse(&[b, mul]).unwrap();
+        assert_eq!(graph.inner().nodes().len(), 3);
+        check(&mut graph);
+
+        let expanded = graph.expand(subgraph).unwrap();
+        assert_eq!(expanded.len(), 2);
+        assert_eq!(graph.inner().nodes().len(), 4);
+        assert!(graph.metadata().get(subgraph).is_none());
+        check(&mut graph);
+
+        let inner = Graph::with_root(ConstantNode::Unsigned(1));
+        let subgraph = ::nodes::SubgraphNode::new(inner, vec![]);
+        let mut graph = UIGraph::new(FontId::default(), subgraph, 0., 0.);
+        assert_eq!(graph.expand(graph.root()), None);
+        assert_eq!(graph.inner().nodes().len(), 1);
+        assert!(!graph.undo());
+    }
+
+    #[test]
+    fn undo_redo() {
+        let mut graph = UIGraph::new(FontId::default(), AddNode, 0., 0.);
+        let root = graph.root();
+        let a = graph.add_node(ConstantNode::Unsigned(2), 0., 0.);
+        let b = graph.add_node(ConstantNode::Unsigned(3), 0., 0.);
+        graph.connect(a, root, 0).unwrap();
+        graph.connect(b, root, 1).unwrap();
+
+        let check = |graph: &mut UIGraph, expected: u32| {
+            let output = graph.execute().unwrap();
+            assert_eq!(output.downcast_ref::<One<u32>>(), Some(&One::new(expected)));
+        };
+        check(&mut graph, 5);
+        graph.clear_history();
+        assert!(!graph.undo());
+
+        graph.group(|graph| {
+            graph.remove_node(a);
+            graph.remove_node(b);
+            Some(())
+        });
+        assert_eq!(graph.inner().nodes().len(), 1);
+        assert!(graph.undo());
+        assert_eq!(graph.inner().nodes().len(), 3);
+        check(&mut graph, 5);
+        assert!(graph.redo());
+        assert_eq!(graph.inner().nodes().len(), 1);
+        assert!(!graph.redo());
+        assert!(graph.undo());
+        check(&mut graph, 5);
+
+        // the restored nodes have new ids
+        let input = |graph: &UIGraph, index| {
+            let connection = graph
+                .inner()
+                .connections()
+                .iter()
+                .find(|c| c.input == index);
+            connection.unwrap().from
+        };
+        let a = input(&graph, 0);
+        let position = |graph: &UIGraph| graph.metadata()[a].position.x;
+        graph.move_nodes(&[a], 10., 0.);
+        graph.move_nodes(&[a], 10., 0.);
+        assert_eq!(position(&graph), 20.);
+        graph.checkpoint();
+        graph.move_nodes(&[a], 10., 0.);
+        assert!(graph.undo());
+        assert_eq!(position(&graph), 20.);
+        assert!(graph.undo());
+        assert_eq!(position(&graph), 0.);
+        assert!(graph.redo());
+        assert_eq!(position(&graph), 20.);
+
+        // moving nodes doesn't run type inference again
+        graph.inferred.clear();
+        graph.move_nodes(&[a], 10., 0.);
+        assert!(graph.undo());
+        assert!(graph.inferred.is_empty());
+
+        graph.replace_node(a, Box::new(ConstantNode::Unsigned(1)));
+        graph.replace_node(a, Box::new(ConstantNode::Unsigned(10)));
+        check(&mut graph, 13);
+        assert!(graph.undo());
+        check(&mut graph, 5);
+
+        let collapsed = input(&graph, 1);
+        graph.collapse(&[collapsed]).unwrap();
+        check(&mut graph, 5);
+        assert!(graph.undo());
+        assert!(graph
+            .inner()
+            .nodes()
+            .values()
+            .all(|n| n.is::<AddNode>() || n.is::<ConstantNode>()));
+        check(&mut graph, 5);
+    }
+}
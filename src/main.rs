@@ -25,6 +25,41 @@ vec4 effect(vec4 color, Image texture, vec2 texture_coords, vec2 screen_coords)
 "#;
 
 fn main() {
+    let resources_folder = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("resources");
+    let graph_path = resources_folder.join("graph2.json");
+
+    // these print something and exit without opening the editor
+    let mut args = std::env::args().skip(1);
+    match args.next().as_deref() {
+        // `--dot [path]` prints a saved graph as Graphviz source
+        Some("--dot") => {
+            let path = args
+                .next()
+                .map(std::path::PathBuf::from)
+                .unwrap_or(graph_path);
+            match demoloops_ui::file::load_file::<UIGraph>(&path) {
+                Ok(graph) => print!("{}", graph.to_dot()),
+                Err(err) => {
+                    eprintln!("{}", err);
+                    std::process::exit(1);
+                }
+            }
+            return;
+        }
+        // markdown reference for every node in the palette
+        Some("--docs") => {
+            print!("{}", registry().to_markdown());
+            return;
+        }
+        // the same as JSON, for tooling
+        Some("--catalogue") => {
+            let catalogue = registry().catalogue();
+            println!("{}", serde_json::to_string_pretty(&catalogue).unwrap());
+            return;
+        }
+        _ => {}
+    }
+
     let (width, height) = (1920., 1080.);
     let event_loop = glutin::event_loop::EventLoop::new();
     let wb = glutin::window::WindowBuilder::new()
@@ -34,9 +69,6 @@ fn main() {
     let mut ctx = solstice::Context::new(ctx);
     let mut ctx_2d = solstice_2d::Graphics::new(&mut ctx, width, height).unwrap();
 
-    let resources_folder = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("resources");
-    let graph_path = resources_folder.join("graph2.json");
-
     let font = ab_glyph::FontVec::try_from_vec({
         let path = resources_folder.join("Roboto-Regular.ttf");
         std::fs::read(path).unwrap()
@@ -44,10 +76,21 @@ fn main() {
     .unwrap();
     let font = ctx_2d.add_font(font);
 
+    // a graph that fails to load might be from a newer version so it shouldn't be overwritten
+    let mut overwrite = true;
     let mut graph = {
         std::fs::read(&graph_path)
             .map_err(eyre::Error::from)
-            .and_then(|data| serde_json::from_slice(&data).map_err(eyre::Error::from))
+            .and_then(|data| {
+                let graph = demoloops_ui::file::load::<UIGraph>(&data);
+                overwrite = graph.is_ok();
+                Ok(graph?)
+            })
+            .and_then(|mut graph| {
+                graph.inner().validate()?;
+                graph.infer_types();
+                Ok(graph)
+            })
             .unwrap_or_else(|err| {
                 eprintln!("{}", err);
 
@@ -64,34 +107,40 @@ fn main() {
 
                 let _text = graph.add_node(ConstantNode::Text(SHADER_SRC.to_owned()), 100., 600.);
 
-                graph.connect(count, range, 0);
+                graph.connect(count, range, 0).unwrap();
 
-                graph.connect(range, ratio, 0);
-                graph.connect(count, ratio, 1);
+                graph.connect(range, ratio, 0).unwrap();
+                graph.connect(count, ratio, 1).unwrap();
 
-                graph.connect(offset, multiply, 0);
-                graph.connect(ratio, multiply, 1);
+                graph.connect(offset, multiply, 0).unwrap();
+                graph.connect(ratio, multiply, 1).unwrap();
 
-                graph.connect(multiply, rect, 0);
-                graph.connect(y, rect, 1);
-                graph.connect(d, rect, 2);
-                graph.connect(d, rect, 3);
-                graph.connect(rect, draw, 0);
+                graph.connect(multiply, rect, 0).unwrap();
+                graph.connect(y, rect, 1).unwrap();
+                graph.connect(d, rect, 2).unwrap();
+                graph.connect(d, rect, 3).unwrap();
+                graph.connect(rect, draw, 0).unwrap();
 
-                graph.connect(draw, graph.root(), 0);
+                graph.connect(draw, graph.root(), 0).unwrap();
 
                 graph
             })
     };
+    // building the starting graph isn't something to undo
+    graph.clear_history();
     let mut resources_cache = command::ResourcesCache::default();
     let mut canvas = solstice_2d::Canvas::new(&mut ctx, width, height).unwrap();
 
     let mut ui_state = UIState::None;
     let mut mouse_position = PhysicalPosition::new(0., 0.);
+    let mut modifiers = glutin::event::ModifiersState::empty();
     // let mut selected_nodes = std::collections::HashSet::new();
 
     let mut show_graph = true;
     let mut times = std::collections::VecDeque::with_capacity(60);
+    // animations follow real time rather than the frame rate
+    let started = std::time::Instant::now();
+    let mut eval_context = ::nodes::EvalContext::default().with_resolution(width as _, height as _);
 
     // let _execution_thread_handle = std::thread::spawn({
     //     let graph = graph.clone();
@@ -117,9 +166,28 @@ fn main() {
                         WindowEvent::Resized(size) => {
                             ctx.set_viewport(0, 0, size.width as _, size.height as _);
                             ctx_2d.set_width_height(size.width as _, size.height as _);
+                            eval_context.resolution = [size.width, size.height];
                             canvas = solstice_2d::Canvas::new(&mut ctx, width, height).unwrap();
                         }
                         WindowEvent::CloseRequested => *control_flow = ControlFlow::Exit,
+                        WindowEvent::ModifiersChanged(state) => modifiers = state,
+                        WindowEvent::KeyboardInput {
+                            input:
+                                KeyboardInput {
+                                    state: ElementState::Pressed,
+                                    virtual_keycode: Some(VirtualKeyCode::Z),
+                                    ..
+                                },
+                            ..
+                        } if modifiers.ctrl() => {
+                            if modifiers.shift() {
+                                graph.redo();
+                            } else {
+                                graph.undo();
+                            }
+                            // whatever was selected may not exist anymore
+                            ui_state = UIState::None;
+                        }
                         WindowEvent::KeyboardInput {
                             input:
                                 KeyboardInput {
@@ -153,6 +221,15 @@ fn main() {
                                 },
                             )
                         }
+                        WindowEvent::ReceivedCharacter(c) => {
+                            ui_state = ui_state.clone().handle_event(
+                                UIEvent::ReceivedCharacter(c),
+                                UIContext {
+                                    mouse_position,
+                                    graph: &mut graph,
+                                },
+                            )
+                        }
                         WindowEvent::CursorMoved { position, .. } => {
                             ui_state = ui_state.clone().handle_event(
                                 UIEvent::MouseMoved(position),
@@ -170,9 +247,12 @@ fn main() {
             Event::MainEventsCleared => {
                 window.request_redraw();
             }
+            Event::LoopDestroyed if !overwrite => {
+                eprintln!("not saving over {}", graph_path.display());
+            }
             Event::LoopDestroyed => match std::fs::File::create(&graph_path) {
                 Ok(writer) => {
-                    if let Err(err) = serde_json::to_writer_pretty(writer, &graph) {
+                    if let Err(err) = demoloops_ui::file::save(writer, &graph) {
                         eprintln!("{}", err);
                     }
                 }
@@ -183,7 +263,16 @@ fn main() {
             Event::RedrawRequested(_) => {
                 ctx.clear_color(0.1, 0.1, 0.1, 1.);
                 ctx.clear();
-                {
+                let eval_error = {
+                    let time = started.elapsed().as_secs_f32();
+                    eval_context = ::nodes::EvalContext {
+                        frame: eval_context.frame + 1,
+                        time,
+                        delta: time - eval_context.time,
+                        ..eval_context
+                    };
+                    graph.set_context(eval_context);
+
                     let start = std::time::Instant::now();
                     let result = graph.execute();
                     let elapsed = start.elapsed();
@@ -191,21 +280,27 @@ fn main() {
                         times.pop_front();
                     }
                     times.push_back(elapsed);
-                    match result {
-                        Ok(output) => {
-                            let commands = output.downcast::<One<Vec<command::Command>>>().unwrap();
-                            command::Command::batch_execute(
-                                &mut ctx,
-                                &mut ctx_2d,
-                                &mut resources_cache,
-                                &*commands,
-                            );
-                        }
-                        Err(_err) => {
-                            // eprintln!("{:?}", err);
-                        }
+                    if let Some(output) = result {
+                        let commands = output.downcast::<One<Vec<command::Command>>>().unwrap();
+                        command::Command::batch_execute(
+                            &mut ctx,
+                            &mut ctx_2d,
+                            &mut resources_cache,
+                            &*commands,
+                        );
                     }
-                }
+                    // the failures themselves are shown on the nodes
+                    let failed = graph
+                        .status()
+                        .values()
+                        .filter(|status| matches!(status, ::nodes::NodeStatus::Failed(_)))
+                        .count();
+                    if failed > 0 {
+                        Some(format!("{} node(s) failed", failed))
+                    } else {
+                        None
+                    }
+                };
 
                 if show_graph {
                     let mut g = ctx_2d.lock(&mut ctx);
@@ -219,6 +314,14 @@ fn main() {
                         16.,
                         solstice_2d::Rectangle::new(width / 2., 0., width / 2., 50.),
                     );
+                    if let Some(eval_error) = eval_error {
+                        g.print(
+                            eval_error,
+                            font,
+                            16.,
+                            solstice_2d::Rectangle::new(width / 2., 20., width / 2., 50.),
+                        );
+                    }
                     graph.render(&mut g);
                     ui_state.render(
                         &mut g,
@@ -236,40 +339,12 @@ fn main() {
                 }
 
                 window.swap_buffers().expect("terrible, terrible damage");
-                ::nodes::GlobalNode::incr();
             }
             _ => {}
         }
     });
 }
 
-const POSSIBLE_NODES: once_cell::sync::Lazy<Vec<Box<dyn Node>>> =
-    once_cell::sync::Lazy::new(|| {
-        vec![
-            Box::new(::nodes::ConstantNode::Unsigned(0)),
-            Box::new(::nodes::GlobalNode),
-            Box::new(::nodes::AddNode),
-            Box::new(::nodes::MultiplyNode),
-            Box::new(::nodes::DivisionNode),
-            Box::new(::nodes::ModuloNode),
-            Box::new(::nodes::RangeNode),
-            Box::new(::nodes::RepeatNode),
-            Box::new(::nodes::RatioNode),
-            Box::new(::nodes::SineNode),
-            Box::new(::nodes::CosNode),
-            Box::new(::nodes::ToFloatNode),
-            Box::new(ColorNode),
-            Box::new(HSLNode),
-            Box::new(RectangleNode),
-            Box::new(RegularPolygonNode),
-            Box::new(WhiteTextureNode),
-            Box::new(NoiseTextureNode),
-            Box::new(ShaderNode::default()),
-            Box::new(DrawNode),
-            Box::new(ClearNode),
-        ]
-    });
-
 #[derive(Debug, Clone)]
 enum Action {
     Move,
@@ -292,12 +367,7 @@ impl NewNodeContext {
     pub fn bounds(&self) -> solstice_2d::Rectangle {
         let ox = self.origin.x as f32;
         let oy = self.origin.y as f32;
-        solstice_2d::Rectangle::new(
-            ox,
-            oy,
-            100.,
-            POSSIBLE_NODES.len() as f32 * Self::ELEMENT_HEIGHT,
-        )
+        solstice_2d::Rectangle::new(ox, oy, 100., registry().len() as f32 * Self::ELEMENT_HEIGHT)
     }
 
     pub fn item_bounds(&self, index: usize) -> solstice_2d::Rectangle {
@@ -315,6 +385,7 @@ impl NewNodeContext {
 #[derive(Debug, Copy, Clone)]
 struct NewConnectionContext {
     from: NodeID,
+    output: usize,
 }
 
 #[derive(Debug, Copy, Clone)]
@@ -339,6 +410,14 @@ enum UIEvent {
         button: glutin::event::MouseButton,
     },
     MouseMoved(PhysicalPosition<f64>),
+    ReceivedCharacter(char),
+}
+
+fn is_expression(graph: &UIGraph, node_id: NodeID) -> bool {
+    match graph.inner().nodes().get(node_id) {
+        Some(node) => node.is::<ExpressionNode>(),
+        None => false,
+    }
 }
 
 struct UIContext<'a> {
@@ -377,17 +456,24 @@ impl UIState {
                 g.draw_with_color(bounds, [0.3, 0.3, 0.3, 1.]);
                 let mx = context.mouse_position.x as f32;
                 let my = context.mouse_position.y as f32;
-                for (index, node) in POSSIBLE_NODES.iter().enumerate() {
+                for (index, entry) in registry().entries().iter().enumerate() {
                     let bounds = ctx.item_bounds(index);
-                    g.print(node.name(), solstice_2d::FontId::default(), 16., bounds);
+                    g.print(entry.name(), solstice_2d::FontId::default(), 16., bounds);
                     if rect_contains(&bounds, mx, my) {
                         g.stroke_with_color(bounds, [1., 1., 0., 0.75]);
                     }
                 }
             }
             UIState::NewConnection(ctx) => {
+                let outputs = context
+                    .graph
+                    .inner()
+                    .nodes()
+                    .get(ctx.from)
+                    .map(|node| node.outputs().len())
+                    .unwrap_or(1);
                 if let Some(metadata) = context.graph.metadata().get(ctx.from) {
-                    let from = rect_center(&metadata.output());
+                    let from = rect_center(&metadata.output(ctx.output, outputs));
                     let to = Position {
                         x: context.mouse_position.x as f32,
                         y: context.mouse_position.y as f32,
@@ -447,6 +533,7 @@ impl UIState {
         match self {
             UIState::None => match event {
                 UIEvent::KeyboardInput { .. } => self,
+                UIEvent::ReceivedCharacter(_) => self,
                 UIEvent::MouseInput { state, button } => match state {
                     ElementState::Pressed => {
                         let x = mouse_position.x as f32;
@@ -458,6 +545,15 @@ impl UIState {
                         match button {
                             MouseButton::Left => {
                                 if let Some((node_id, metadata)) = clicked {
+                                    let outputs = graph
+                                        .inner()
+                                        .nodes()
+                                        .get(node_id)
+                                        .map(|node| node.outputs().len())
+                                        .unwrap_or(1);
+                                    let output = (0..outputs).find(|index| {
+                                        rect_contains(&metadata.output(*index, outputs), x, y)
+                                    });
                                     if rect_contains(&metadata.top_bar(), x, y) {
                                         Self::NodeAction(ActionContext {
                                             node_id,
@@ -468,8 +564,11 @@ impl UIState {
                                             node_id,
                                             action: Action::Resize,
                                         })
-                                    } else if rect_contains(&metadata.output(), x, y) {
-                                        Self::NewConnection(NewConnectionContext { from: node_id })
+                                    } else if let Some(output) = output {
+                                        Self::NewConnection(NewConnectionContext {
+                                            from: node_id,
+                                            output,
+                                        })
                                     } else if let Some(node) = graph
                                         .inner()
                                         .nodes()
@@ -485,6 +584,17 @@ impl UIState {
                                             node_id,
                                             action: Action::Edit { buffer },
                                         })
+                                    } else if let Some(node) = graph
+                                        .inner()
+                                        .nodes()
+                                        .get(node_id)
+                                        .and_then(|n| n.downcast_ref::<ExpressionNode>())
+                                    {
+                                        let buffer = node.expression().to_owned();
+                                        Self::NodeAction(ActionContext {
+                                            node_id,
+                                            action: Action::Edit { buffer },
+                                        })
                                     } else {
                                         self
                                     }
@@ -513,6 +623,22 @@ impl UIState {
             UIState::NodeAction(mut action) => match event {
                 UIEvent::KeyboardInput { state, key_code } => {
                     match &mut action.action {
+                        // expressions are typed with `ReceivedCharacter` instead
+                        Action::Edit { buffer } if is_expression(graph, action.node_id) => {
+                            use glutin::event::VirtualKeyCode;
+                            match (state, key_code) {
+                                (ElementState::Pressed, VirtualKeyCode::Back) => {
+                                    buffer.pop();
+                                    let node = ExpressionNode::new(buffer.clone());
+                                    graph.replace_node(action.node_id, Box::new(node));
+                                }
+                                (ElementState::Pressed, VirtualKeyCode::Return) => {
+                                    graph.checkpoint();
+                                    return UIState::None;
+                                }
+                                _ => {}
+                            }
+                        }
                         Action::Edit { buffer } => match state {
                             ElementState::Pressed => {
                                 use glutin::event::VirtualKeyCode;
@@ -533,23 +659,26 @@ impl UIState {
                                         buffer.pop();
                                     }
                                     VirtualKeyCode::Return | VirtualKeyCode::Escape => {
+                                        graph.checkpoint();
                                         return UIState::None;
                                     }
                                     _ => {
                                         return UIState::NodeAction(action);
                                     }
                                 };
-                                let node = graph
-                                    .node_mut(action.node_id)
-                                    .and_then(|n| n.downcast_mut::<ConstantNode>());
-                                if let Some(node) = node {
-                                    if let Ok(v) = buffer.parse::<u32>() {
-                                        *node = ConstantNode::Unsigned(v);
+                                let is_constant = match graph.inner().nodes().get(action.node_id) {
+                                    Some(node) => node.is::<ConstantNode>(),
+                                    None => false,
+                                };
+                                if is_constant {
+                                    let node = if let Ok(v) = buffer.parse::<u32>() {
+                                        ConstantNode::Unsigned(v)
                                     } else if let Ok(v) = buffer.parse::<f32>() {
-                                        *node = ConstantNode::Float(v);
+                                        ConstantNode::Float(v)
                                     } else {
-                                        *node = ConstantNode::Text(buffer.clone());
-                                    }
+                                        ConstantNode::Text(buffer.clone())
+                                    };
+                                    graph.replace_node(action.node_id, Box::new(node));
                                 }
                             }
                             ElementState::Released => {}
@@ -558,10 +687,23 @@ impl UIState {
                     }
                     UIState::NodeAction(action)
                 }
+                UIEvent::ReceivedCharacter(c) => {
+                    if let Action::Edit { buffer } = &mut action.action {
+                        if !c.is_control() && is_expression(graph, action.node_id) {
+                            buffer.push(c);
+                            let node = ExpressionNode::new(buffer.clone());
+                            graph.replace_node(action.node_id, Box::new(node));
+                        }
+                    }
+                    UIState::NodeAction(action)
+                }
                 UIEvent::MouseInput { state, .. } => match state {
                     ElementState::Pressed => UIState::NodeAction(action),
                     ElementState::Released => match action.action {
-                        Action::Move | Action::Resize => UIState::None,
+                        Action::Move | Action::Resize => {
+                            graph.checkpoint();
+                            UIState::None
+                        }
                         Action::Edit { .. } => UIState::NodeAction(action),
                     },
                 },
@@ -571,16 +713,10 @@ impl UIState {
                     let node_id = action.node_id;
                     match &action.action {
                         Action::Move => {
-                            if let Some(selected) = graph.metadata_mut(node_id) {
-                                selected.position.x += delta_x as f32;
-                                selected.position.y += delta_y as f32;
-                            }
+                            graph.move_nodes(&[node_id], delta_x as f32, delta_y as f32);
                         }
                         Action::Resize => {
-                            if let Some(selected) = graph.metadata_mut(node_id) {
-                                selected.dimensions.width += delta_x as f32;
-                                selected.dimensions.height += delta_y as f32;
-                            }
+                            graph.resize_node(node_id, delta_x as f32, delta_y as f32);
                         }
                         Action::Edit { .. } => {}
                     }
@@ -589,6 +725,7 @@ impl UIState {
             },
             UIState::NewNode(ctx) => match event {
                 UIEvent::KeyboardInput { .. } => self,
+                UIEvent::ReceivedCharacter(_) => self,
                 UIEvent::MouseMoved(_) => self,
                 UIEvent::MouseInput { state, button } => match (state, button) {
                     (ElementState::Pressed, MouseButton::Left) => {
@@ -596,15 +733,16 @@ impl UIState {
                         let mx = mouse_position.x as f32;
                         let my = mouse_position.y as f32;
                         if rect_contains(&bounds, mx, my) {
-                            let clicked =
-                                POSSIBLE_NODES.iter().enumerate().find_map(|(index, node)| {
+                            let clicked = registry().entries().iter().enumerate().find_map(
+                                |(index, entry)| {
                                     let bounds = ctx.item_bounds(index);
                                     if rect_contains(&bounds, mx, my) {
-                                        Some(node.clone())
+                                        Some(entry.create())
                                     } else {
                                         None
                                     }
-                                });
+                                },
+                            );
                             if let Some(node) = clicked {
                                 let x = ctx.origin.x as f32;
                                 let y = ctx.origin.y as f32;
@@ -623,6 +761,7 @@ impl UIState {
             },
             UIState::NewConnection(ctx) => match event {
                 UIEvent::KeyboardInput { .. } => self,
+                UIEvent::ReceivedCharacter(_) => self,
                 UIEvent::MouseMoved(_) => self,
                 UIEvent::MouseInput { state, button } => match (state, button) {
                     (ElementState::Released, MouseButton::Left) => {
@@ -652,7 +791,11 @@ impl UIState {
                                     .count();
                                 for index in 0..=connections {
                                     if rect_contains(&metadata.input(index), mx, my) {
-                                        graph.connect(ctx.from, to, index);
+                                        if let Err(err) =
+                                            graph.connect_output(ctx.from, ctx.output, to, index)
+                                        {
+                                            eprintln!("{}", err);
+                                        }
                                         break;
                                     }
                                 }
@@ -673,7 +816,11 @@ impl UIState {
                                         });
 
                                     if let Some(input) = input {
-                                        graph.connect(ctx.from, to, input);
+                                        if let Err(err) =
+                                            graph.connect_output(ctx.from, ctx.output, to, input)
+                                        {
+                                            eprintln!("{}", err);
+                                        }
                                     }
                                 }
                             }
@@ -685,6 +832,7 @@ impl UIState {
             },
             UIState::MultiSelect(ctx) => match event {
                 UIEvent::KeyboardInput { .. } => self,
+                UIEvent::ReceivedCharacter(_) => self,
                 UIEvent::MouseMoved(_) => self,
                 UIEvent::MouseInput { state, button } => match (state, button) {
                     (ElementState::Released, MouseButton::Left) => {
@@ -725,24 +873,36 @@ impl UIState {
             UIState::MultiMove(ref mut ctx) => match event {
                 UIEvent::KeyboardInput { state, key_code } => match (state, key_code) {
                     (ElementState::Pressed, glutin::event::VirtualKeyCode::Delete) => {
-                        for node in ctx.selected.iter().copied() {
-                            graph.remove_node(node);
-                        }
+                        graph.group(|graph| {
+                            for node in ctx.selected.iter().copied() {
+                                graph.remove_node(node);
+                            }
+                            Some(())
+                        });
+                        UIState::None
+                    }
+                    (ElementState::Pressed, glutin::event::VirtualKeyCode::G) => {
+                        graph.collapse(&ctx.selected);
+                        UIState::None
+                    }
+                    (ElementState::Pressed, glutin::event::VirtualKeyCode::U) => {
+                        graph.group(|graph| {
+                            for node in ctx.selected.iter().copied() {
+                                graph.expand(node);
+                            }
+                            Some(())
+                        });
                         UIState::None
                     }
                     _ => self,
                 },
+                UIEvent::ReceivedCharacter(_) => self,
                 UIEvent::MouseMoved(position) => {
                     if ctx.moving {
                         let dx = mouse_position.x - position.x;
                         let dy = mouse_position.y - position.y;
 
-                        for node in ctx.selected.iter().copied() {
-                            if let Some(metadata) = graph.metadata_mut(node) {
-                                metadata.position.x -= dx as f32;
-                                metadata.position.y -= dy as f32;
-                            }
-                        }
+                        graph.move_nodes(&ctx.selected, -dx as f32, -dy as f32);
                     }
                     self
                 }
@@ -768,6 +928,7 @@ impl UIState {
                     }
                     (ElementState::Released, MouseButton::Left) => {
                         ctx.moving = false;
+                        graph.checkpoint();
                         self
                     }
                     _ => self,
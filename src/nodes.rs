@@ -15,9 +15,145 @@ pub use color::ColorNode;
 pub use draw::DrawNode;
 pub use hsl::HSLNode;
 pub use noise_texture::NoiseTextureNode;
-pub use rectangle::RectangleNode;
+pub use rectangle::{DecomposeRectangleNode, RectangleNode};
 pub use regular_polygon::RegularPolygonNode;
 pub use screen::ScreenNode;
 pub use shader::ShaderNode;
 pub use transform::{RotationNode, ScalingNode, TranslationNode};
 pub use multiply::ExtendedMultiplyNode;
+
+// The built-in nodes plus the ones defined here. Drives the palette, `--docs` and `--catalogue`.
+pub fn registry() -> &'static ::nodes::Registry {
+    use ::nodes::NodeEntry;
+    static REGISTRY: once_cell::sync::Lazy<::nodes::Registry> = once_cell::sync::Lazy::new(|| {
+        let mut registry = ::nodes::Registry::builtin();
+        registry
+            .register(NodeEntry {
+                category: "math",
+                description: "Multiplies two numbers or combines two transforms.",
+                inputs: &[("lhs", "The first factor."), ("rhs", "The second factor.")],
+                constructor: || Box::new(ExtendedMultiplyNode),
+            })
+            .register(NodeEntry {
+                category: "color",
+                description: "A color from red, green, blue and alpha between 0 and 1.",
+                inputs: &[
+                    ("red", "The red channel."),
+                    ("green", "The green channel."),
+                    ("blue", "The blue channel."),
+                    ("alpha", "The opacity."),
+                ],
+                constructor: || Box::new(ColorNode),
+            })
+            .register(NodeEntry {
+                category: "color",
+                description: "A color from hue, saturation and lightness.",
+                inputs: &[
+                    ("hue", "The hue as an angle in radians."),
+                    ("saturation", "From 0 to 1. Defaults to 1."),
+                    ("light", "From 0 to 1. Defaults to 0.5."),
+                ],
+                constructor: || Box::new(HSLNode),
+            })
+            .register(NodeEntry {
+                category: "geometry",
+                description: "A rectangle from its top left corner and size.",
+                inputs: &[
+                    ("x", "The left edge."),
+                    ("y", "The top edge."),
+                    ("width", "The width."),
+                    ("height", "The height."),
+                ],
+                constructor: || Box::new(RectangleNode),
+            })
+            .register(NodeEntry {
+                category: "geometry",
+                description: "Splits a rectangle into its position and size.",
+                inputs: &[("rectangle", "The rectangle to split.")],
+                constructor: || Box::new(DecomposeRectangleNode),
+            })
+            .register(NodeEntry {
+                category: "geometry",
+                description: "A polygon with equal sides around a center point.",
+                inputs: &[
+                    ("x", "The center's x coordinate. Defaults to 0."),
+                    ("y", "The center's y coordinate. Defaults to 0."),
+                    ("vertex_count", "The number of corners."),
+                    ("radius", "The distance from the center to each corner."),
+                ],
+                constructor: || Box::new(RegularPolygonNode),
+            })
+            .register(NodeEntry {
+                category: "transform",
+                description: "Moves geometry.",
+                inputs: &[
+                    ("x", "Horizontal offset. Defaults to 0."),
+                    ("y", "Vertical offset. Defaults to 0."),
+                    ("z", "Depth offset. Defaults to 0."),
+                ],
+                constructor: || Box::new(TranslationNode),
+            })
+            .register(NodeEntry {
+                category: "transform",
+                description: "Rotates geometry by angles in radians.",
+                inputs: &[
+                    ("roll", "Rotation around the x axis. Defaults to 0."),
+                    ("pitch", "Rotation around the y axis. Defaults to 0."),
+                    ("yaw", "Rotation around the z axis. Defaults to 0."),
+                ],
+                constructor: || Box::new(RotationNode),
+            })
+            .register(NodeEntry {
+                category: "transform",
+                description: "Scales geometry.",
+                inputs: &[
+                    ("x", "Horizontal scale. Defaults to 1."),
+                    ("y", "Vertical scale. Defaults to 1."),
+                    ("z", "Depth scale. Defaults to 1."),
+                ],
+                constructor: || Box::new(ScalingNode),
+            })
+            .register(NodeEntry {
+                category: "drawing",
+                description: "Perlin noise texture settings.",
+                inputs: &[
+                    ("seed", "Picks the noise pattern. Defaults to 0."),
+                    ("width", "Width in pixels. Defaults to 64."),
+                    ("height", "Height in pixels. Defaults to 64."),
+                ],
+                constructor: || Box::new(NoiseTextureNode),
+            })
+            .register(NodeEntry {
+                category: "drawing",
+                description: "A GLSL shader. Its uniforms become extra inputs once it has run.",
+                inputs: &[("shader text", "The shader source.")],
+                constructor: || Box::new(ShaderNode::default()),
+            })
+            .register(NodeEntry {
+                category: "drawing",
+                description: "A command that draws geometry.",
+                inputs: &[
+                    ("geometry", "A rectangle or regular polygon."),
+                    ("transform", "Applied to the geometry."),
+                    ("color", "Defaults to white."),
+                    ("texture", "A noise texture."),
+                    ("shader", "Replaces the default shader."),
+                ],
+                constructor: || Box::new(DrawNode),
+            })
+            .register(NodeEntry {
+                category: "drawing",
+                description: "A command that fills the screen with a color.",
+                inputs: &[("color", "The color to fill with.")],
+                constructor: || Box::new(ClearNode),
+            })
+            .register(NodeEntry {
+                category: "drawing",
+                description: "Collects draw and clear commands into a frame, in input order.",
+                inputs: &[("command", "Commands to run.")],
+                constructor: || Box::new(ScreenNode),
+            });
+        registry
+    });
+    &REGISTRY
+}
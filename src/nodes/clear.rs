@@ -1,10 +1,12 @@
-use nodes::{FromAnyProto, Node, NodeInput, NodeOutput, OneOrMany, PossibleInputs};
+use nodes::{
+    AnyClone, FromAnyProto, InputError, Node, NodeInput, NodeOutput, OneOrMany, PossibleInputs,
+    TypeSet,
+};
 use solstice_2d::Color;
-use std::any::Any;
 
 type ClearInput = (OneOrMany<Color>,);
 
-fn op((color,): ClearInput) -> Box<dyn Any> {
+fn op((color,): ClearInput) -> Box<dyn AnyClone> {
     use ::nodes::one_many::op1 as op;
     op(color, crate::command::ClearCommand::new).into_boxed_inner()
 }
@@ -21,9 +23,17 @@ impl NodeInput for ClearNode {
 }
 
 impl NodeOutput for ClearNode {
-    fn op(&self, inputs: &mut Vec<Box<dyn Any>>) -> Result<Box<dyn Any>, ()> {
+    fn op(
+        &self,
+        inputs: &mut Vec<Box<dyn AnyClone>>,
+        _ctx: &nodes::EvalContext,
+    ) -> Result<Box<dyn AnyClone>, InputError> {
         FromAnyProto::from_any(nodes::InputStack::new(inputs, ..)).map(op)
     }
+
+    fn output_types(&self, _output: usize, _inputs: &[TypeSet]) -> TypeSet {
+        TypeSet::one_or_many::<crate::command::ClearCommand>()
+    }
 }
 
 #[typetag::serde]
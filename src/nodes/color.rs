@@ -1,6 +1,8 @@
-use nodes::{FromAnyProto, Node, NodeInput, NodeOutput, OneOrMany, PossibleInputs};
+use nodes::{
+    AnyClone, FromAnyProto, InputError, Node, NodeInput, NodeOutput, OneOrMany, PossibleInputs,
+    TypeSet,
+};
 use solstice_2d::Color;
-use std::any::Any;
 
 type ColorInput = (
     OneOrMany<f32>,
@@ -9,7 +11,7 @@ type ColorInput = (
     OneOrMany<f32>,
 );
 
-fn op((r, g, b, a): ColorInput) -> Box<dyn Any> {
+fn op((r, g, b, a): ColorInput) -> Box<dyn AnyClone> {
     nodes::one_many::op4(r, g, b, a, Color::new).into_boxed_inner()
 }
 
@@ -26,9 +28,17 @@ impl NodeInput for ColorNode {
 }
 
 impl NodeOutput for ColorNode {
-    fn op(&self, inputs: &mut Vec<Box<dyn Any>>) -> Result<Box<dyn Any>, ()> {
+    fn op(
+        &self,
+        inputs: &mut Vec<Box<dyn AnyClone>>,
+        _ctx: &nodes::EvalContext,
+    ) -> Result<Box<dyn AnyClone>, InputError> {
         FromAnyProto::from_any(nodes::InputStack::new(inputs, ..)).map(op)
     }
+
+    fn output_types(&self, _output: usize, _inputs: &[TypeSet]) -> TypeSet {
+        TypeSet::one_or_many::<Color>()
+    }
 }
 
 #[typetag::serde]
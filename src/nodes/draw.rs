@@ -1,5 +1,7 @@
 use crate::command;
-use nodes::{Node, NodeInput, NodeOutput, OneOrMany, PossibleInputs};
+use nodes::{
+    AnyClone, InputError, Node, NodeInput, NodeOutput, OneOrMany, PossibleInputs, TypeSet,
+};
 use solstice_2d::{Color, PerlinTextureSettings, Rectangle, RegularPolygon, Transform3D};
 
 #[derive(nodes::InputComponent, nodes::FromAnyProto)]
@@ -18,7 +20,7 @@ struct DrawNodeInput {
 }
 
 impl DrawNodeInput {
-    fn op(self) -> Box<dyn std::any::Any> {
+    fn op(self) -> Box<dyn AnyClone> {
         use nodes::one_many::{op4, op5};
         let color = self
             .color
@@ -86,9 +88,17 @@ impl NodeInput for DrawNode {
 }
 
 impl NodeOutput for DrawNode {
-    fn op(&self, inputs: &mut Vec<Box<dyn std::any::Any>>) -> Result<Box<dyn std::any::Any>, ()> {
+    fn op(
+        &self,
+        inputs: &mut Vec<Box<dyn AnyClone>>,
+        _ctx: &nodes::EvalContext,
+    ) -> Result<Box<dyn AnyClone>, InputError> {
         nodes::FromAnyProto::from_any(nodes::InputStack::new(inputs, ..)).map(DrawNodeInput::op)
     }
+
+    fn output_types(&self, _output: usize, _inputs: &[TypeSet]) -> TypeSet {
+        TypeSet::one_or_many::<command::DrawCommand>()
+    }
 }
 
 #[typetag::serde]
@@ -103,7 +113,6 @@ mod tests {
     use super::*;
     use crate::command::{DrawCommand, Shader};
     use nodes::One;
-    use std::any::Any;
 
     #[test]
     fn inputs() {
@@ -112,9 +121,9 @@ mod tests {
 
         let geometry = Box::new(One::new(crate::Rectangle::new(0., 0., 100., 100.)));
 
-        let mut inputs: Vec<Box<dyn Any>> = vec![];
+        let mut inputs: Vec<Box<dyn AnyClone>> = vec![];
         inputs.push(Box::new(One::new(SHADER_SRC.to_string())));
-        let shader = shader_node.op(&mut inputs).unwrap();
+        let shader = shader_node.op(&mut inputs, &Default::default()).unwrap();
         assert!((&*shader).is::<One<Shader>>());
 
         inputs.push(geometry);
@@ -123,7 +132,7 @@ mod tests {
         inputs.push(Box::new(Option::<()>::None));
         inputs.push(shader);
         let command = draw_node
-            .op(&mut inputs)
+            .op(&mut inputs, &Default::default())
             .unwrap()
             .downcast::<One<DrawCommand>>()
             .unwrap();
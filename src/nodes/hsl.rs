@@ -1,6 +1,8 @@
-use nodes::{FromAnyProto, InputStack, Node, NodeInput, NodeOutput, OneOrMany, PossibleInputs};
+use nodes::{
+    AnyClone, FromAnyProto, InputError, InputStack, Node, NodeInput, NodeOutput, OneOrMany,
+    PossibleInputs, TypeSet,
+};
 use solstice_2d::Color;
-use std::any::Any;
 
 #[derive(FromAnyProto, nodes::InputComponent)]
 struct HSLInput {
@@ -9,7 +11,7 @@ struct HSLInput {
     light: Option<OneOrMany<f32>>,
 }
 
-fn op(input: HSLInput) -> Box<dyn Any> {
+fn op(input: HSLInput) -> Box<dyn AnyClone> {
     use ::nodes::one_many::op3;
     fn hue_to_rgb(p: f32, q: f32, t: f32) -> f32 {
         // Normalize
@@ -81,9 +83,17 @@ impl NodeInput for HSLNode {
 }
 
 impl NodeOutput for HSLNode {
-    fn op(&self, inputs: &mut Vec<Box<dyn Any>>) -> Result<Box<dyn Any>, ()> {
+    fn op(
+        &self,
+        inputs: &mut Vec<Box<dyn AnyClone>>,
+        _ctx: &nodes::EvalContext,
+    ) -> Result<Box<dyn AnyClone>, InputError> {
         FromAnyProto::from_any(InputStack::new(inputs, ..)).map(op)
     }
+
+    fn output_types(&self, _output: usize, _inputs: &[TypeSet]) -> TypeSet {
+        TypeSet::one_or_many::<Color>()
+    }
 }
 
 #[typetag::serde]
@@ -1,8 +1,7 @@
 use nodes::{
-    FromAnyProto, InputStack, OneOrMany, PossibleInputs,
+    AnyClone, FromAnyProto, InputError, InputStack, OneOrMany, PossibleInputs, TypeSet,
 };
 use solstice_2d::Transform3D;
-use std::any::Any;
 
 #[derive(FromAnyProto, nodes::InputComponent)]
 struct TransformMultiplyInput {
@@ -17,7 +16,7 @@ enum MultiplyInput {
 }
 
 impl MultiplyInput {
-    fn op(self) -> Box<dyn Any> {
+    fn op(self) -> Box<dyn AnyClone> {
         match self {
             MultiplyInput::Standard(v) => v.mul(),
             MultiplyInput::Extended(v) => {
@@ -40,9 +39,25 @@ impl nodes::NodeInput for ExtendedMultiplyNode {
 }
 
 impl nodes::NodeOutput for ExtendedMultiplyNode {
-    fn op(&self, inputs: &mut Vec<Box<dyn Any>>) -> Result<Box<dyn Any>, ()> {
+    fn op(
+        &self,
+        inputs: &mut Vec<Box<dyn AnyClone>>,
+        _ctx: &nodes::EvalContext,
+    ) -> Result<Box<dyn AnyClone>, InputError> {
         nodes::FromAnyProto::from_any(InputStack::new(inputs, ..)).map(MultiplyInput::op)
     }
+
+    fn output_types(&self, _output: usize, inputs: &[TypeSet]) -> TypeSet {
+        let transforms = TypeSet::one_or_many::<Transform3D>();
+        if inputs
+            .iter()
+            .any(|types| types.is_subset(&transforms) && types != &TypeSet::empty())
+        {
+            transforms
+        } else {
+            nodes::ArithmeticNodeInput::output_types(inputs)
+        }
+    }
 }
 
 #[typetag::serde]
@@ -1,6 +1,8 @@
-use nodes::{FromAnyProto, InputComponent, InputStack, OneOrMany, PossibleInputs};
+use nodes::{
+    AnyClone, FromAnyProto, InputComponent, InputError, InputStack, OneOrMany, PossibleInputs,
+    TypeSet,
+};
 use solstice_2d::PerlinTextureSettings;
-use std::any::Any;
 
 #[derive(FromAnyProto, InputComponent)]
 struct NoiseTextureInput {
@@ -9,7 +11,7 @@ struct NoiseTextureInput {
     height: Option<OneOrMany<u32>>,
 }
 
-fn op(v: NoiseTextureInput) -> Box<dyn Any> {
+fn op(v: NoiseTextureInput) -> Box<dyn AnyClone> {
     use nodes::one_many::op3;
     let seed = v.seed.unwrap_or(OneOrMany::One(nodes::One::new(0)));
     let width = v.width.unwrap_or(OneOrMany::One(nodes::One::new(64)));
@@ -41,9 +43,17 @@ impl nodes::NodeInput for NoiseTextureNode {
 }
 
 impl nodes::NodeOutput for NoiseTextureNode {
-    fn op(&self, inputs: &mut Vec<Box<dyn Any>>) -> Result<Box<dyn Any>, ()> {
+    fn op(
+        &self,
+        inputs: &mut Vec<Box<dyn AnyClone>>,
+        _ctx: &nodes::EvalContext,
+    ) -> Result<Box<dyn AnyClone>, InputError> {
         FromAnyProto::from_any(InputStack::new(inputs, ..)).map(op)
     }
+
+    fn output_types(&self, _output: usize, _inputs: &[TypeSet]) -> TypeSet {
+        TypeSet::one_or_many::<PerlinTextureSettings>()
+    }
 }
 
 #[typetag::serde]
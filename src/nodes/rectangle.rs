@@ -1,6 +1,8 @@
-use nodes::{FromAnyProto, Node, NodeInput, NodeOutput, OneOrMany, PossibleInputs};
+use nodes::{
+    AnyClone, FromAnyProto, InputError, Node, NodeInput, NodeOutput, OneOrMany, OutputInfo,
+    Outputs, PossibleInputs, TypeSet,
+};
 use solstice_2d::Rectangle;
-use std::any::Any;
 
 type RectangleInput = (
     OneOrMany<f32>,
@@ -9,7 +11,7 @@ type RectangleInput = (
     OneOrMany<f32>,
 );
 
-fn op((x, y, width, height): RectangleInput) -> Box<dyn Any> {
+fn op((x, y, width, height): RectangleInput) -> Box<dyn AnyClone> {
     use ::nodes::one_many::op4;
     op4(x, y, width, height, Rectangle::new).into_boxed_inner()
 }
@@ -27,9 +29,17 @@ impl NodeInput for RectangleNode {
 }
 
 impl NodeOutput for RectangleNode {
-    fn op(&self, inputs: &mut Vec<Box<dyn Any>>) -> Result<Box<dyn Any>, ()> {
+    fn op(
+        &self,
+        inputs: &mut Vec<Box<dyn AnyClone>>,
+        _ctx: &nodes::EvalContext,
+    ) -> Result<Box<dyn AnyClone>, InputError> {
         FromAnyProto::from_any(nodes::InputStack::new(inputs, ..)).map(op)
     }
+
+    fn output_types(&self, _output: usize, _inputs: &[TypeSet]) -> TypeSet {
+        TypeSet::one_or_many::<Rectangle>()
+    }
 }
 
 #[typetag::serde]
@@ -39,6 +49,72 @@ impl Node for RectangleNode {
     }
 }
 
+type DecomposeRectangleInput = (OneOrMany<Rectangle>,);
+
+fn decompose((rectangle,): DecomposeRectangleInput) -> Box<dyn AnyClone> {
+    use ::nodes::one_many::op1;
+    Box::new(Outputs(vec![
+        op1(rectangle.clone(), |r: Rectangle| r.x).into_boxed_inner(),
+        op1(rectangle.clone(), |r: Rectangle| r.y).into_boxed_inner(),
+        op1(rectangle.clone(), |r: Rectangle| r.width).into_boxed_inner(),
+        op1(rectangle, |r: Rectangle| r.height).into_boxed_inner(),
+    ]))
+}
+
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+pub struct DecomposeRectangleNode;
+
+impl NodeInput for DecomposeRectangleNode {
+    fn inputs(&self) -> PossibleInputs<'static> {
+        use once_cell::sync::Lazy;
+        static CACHE: Lazy<PossibleInputs> =
+            Lazy::new(|| DecomposeRectangleInput::possible_inputs(&["rectangle"]));
+        PossibleInputs::new(&*CACHE.groups)
+    }
+}
+
+impl NodeOutput for DecomposeRectangleNode {
+    fn op(
+        &self,
+        inputs: &mut Vec<Box<dyn AnyClone>>,
+        _ctx: &nodes::EvalContext,
+    ) -> Result<Box<dyn AnyClone>, InputError> {
+        FromAnyProto::from_any(nodes::InputStack::new(inputs, ..)).map(decompose)
+    }
+
+    fn output_types(&self, _output: usize, _inputs: &[TypeSet]) -> TypeSet {
+        TypeSet::one_or_many::<f32>()
+    }
+
+    fn outputs(&self) -> &'static [OutputInfo] {
+        &[
+            OutputInfo {
+                name: "x",
+                ty_name: "f32",
+            },
+            OutputInfo {
+                name: "y",
+                ty_name: "f32",
+            },
+            OutputInfo {
+                name: "width",
+                ty_name: "f32",
+            },
+            OutputInfo {
+                name: "height",
+                ty_name: "f32",
+            },
+        ]
+    }
+}
+
+#[typetag::serde]
+impl Node for DecomposeRectangleNode {
+    fn name(&self) -> &'static str {
+        "decompose rectangle"
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -46,13 +122,13 @@ mod tests {
 
     #[test]
     fn one() {
-        let mut inputs: Vec<Box<dyn Any>> = vec![
+        let mut inputs: Vec<Box<dyn AnyClone>> = vec![
             Box::new(One::new(100f32)),
             Box::new(One::new(100f32)),
             Box::new(One::new(100f32)),
             Box::new(One::new(100f32)),
         ];
-        let output = RectangleNode::default().op(&mut inputs);
+        let output = RectangleNode::default().op(&mut inputs, &Default::default());
         let rect = output.unwrap().downcast::<One<Rectangle>>();
         assert!(rect.is_ok())
     }
@@ -61,9 +137,9 @@ mod tests {
     fn many() {
         let count = 4usize;
         let mut inputs = (0..count)
-            .map::<Box<dyn Any>, _>(|_| Box::new(Many::from(vec![1f32, 2., 3., 4.])))
+            .map::<Box<dyn AnyClone>, _>(|_| Box::new(Many::from(vec![1f32, 2., 3., 4.])))
             .collect::<Vec<_>>();
-        let output = RectangleNode::default().op(&mut inputs);
+        let output = RectangleNode::default().op(&mut inputs, &Default::default());
         let rects = output.unwrap().downcast::<Many<Rectangle>>();
         let rects = rects.unwrap().collect::<Vec<_>>();
         assert_eq!(count, rects.len());
@@ -71,7 +147,7 @@ mod tests {
 
     #[test]
     fn best_match() {
-        let inputs: Vec<Box<dyn Any>> = vec![
+        let inputs: Vec<Box<dyn AnyClone>> = vec![
             Box::new(One::new(1f32)),
             Box::new(One::new(2f32)),
             Box::new(One::new(3f32)),
@@ -81,4 +157,13 @@ mod tests {
         let group = input_info.best_match(&inputs).expect("expected error");
         assert_eq!(3, group.score(&inputs));
     }
+
+    #[test]
+    fn decompose() {
+        let mut inputs: Vec<Box<dyn AnyClone>> =
+            vec![Box::new(One::new(Rectangle::new(1., 2., 3., 4.)))];
+        let output = DecomposeRectangleNode.op(&mut inputs, &Default::default()).unwrap();
+        let height = Outputs::select(&*output, 3).unwrap();
+        assert_eq!(4., height.downcast::<One<f32>>().unwrap().inner());
+    }
 }
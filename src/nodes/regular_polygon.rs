@@ -1,6 +1,5 @@
-use nodes::{FromAnyProto, Node, NodeInput, NodeOutput, OneOrMany, PossibleInputs};
+use nodes::{AnyClone, FromAnyProto, InputError, Node, NodeInput, NodeOutput, OneOrMany, PossibleInputs, TypeSet};
 use solstice_2d::RegularPolygon;
-use std::any::Any;
 
 #[derive(FromAnyProto, nodes::InputComponent)]
 struct RegularPolygonInput {
@@ -11,7 +10,7 @@ struct RegularPolygonInput {
 }
 
 impl RegularPolygonInput {
-    fn op(self) -> Box<dyn Any> {
+    fn op(self) -> Box<dyn AnyClone> {
         let Self { x, y, vertex_count, radius } = self;
         let x = x.unwrap_or(OneOrMany::One(nodes::One::new(0.)));
         let y = y.unwrap_or(OneOrMany::One(nodes::One::new(0.)));
@@ -33,9 +32,17 @@ impl NodeInput for RegularPolygonNode {
 }
 
 impl NodeOutput for RegularPolygonNode {
-    fn op(&self, inputs: &mut Vec<Box<dyn Any>>) -> Result<Box<dyn Any>, ()> {
+    fn op(
+        &self,
+        inputs: &mut Vec<Box<dyn AnyClone>>,
+        _ctx: &nodes::EvalContext,
+    ) -> Result<Box<dyn AnyClone>, InputError> {
         FromAnyProto::from_any(nodes::InputStack::new(inputs, ..)).map(RegularPolygonInput::op)
     }
+
+    fn output_types(&self, _output: usize, _inputs: &[TypeSet]) -> TypeSet {
+        TypeSet::one_or_many::<RegularPolygon>()
+    }
 }
 
 #[typetag::serde]
@@ -1,6 +1,8 @@
 use crate::command::*;
-use nodes::{FromAnyProto, InputComponent, InputGroup, InputStack, OneOrMany, PossibleInputs};
-use std::any::Any;
+use nodes::{
+    AnyClone, FromAnyProto, InputComponent, InputError, InputGroup, InputStack, OneOrMany,
+    PossibleInputs, TypeSet,
+};
 
 struct ScreenInput {
     commands: Vec<Command>,
@@ -13,16 +15,22 @@ enum CommandInput {
     Clear(nodes::OneOrMany<ClearCommand>),
 }
 
-fn op(input: ScreenInput) -> Box<dyn Any> {
+fn op(input: ScreenInput) -> Box<dyn AnyClone> {
     Box::new(nodes::One::new(input.commands))
 }
 
 impl FromAnyProto for ScreenInput {
-    fn from_any(inputs: InputStack<'_, Box<dyn Any>>) -> Result<Self, ()> {
-        if inputs.deref_iter().all(CommandInput::is) {
+    fn from_any(inputs: InputStack<'_, Box<dyn AnyClone>>) -> Result<Self, InputError> {
+        let mismatch = inputs
+            .deref_iter()
+            .enumerate()
+            .find(|(_, input)| !CommandInput::is(*input));
+        if let Some((index, input)) = mismatch {
+            Err(InputError::mismatch::<CommandInput>(index, input))
+        } else {
             let commands = inputs
                 .consume()
-                .map(CommandInput::downcast)
+                .map(|input| CommandInput::downcast(input))
                 .map(Result::unwrap);
             // TODO: capacity might be calculable by summing all size_hints
             let mut acc = Vec::new();
@@ -43,8 +51,6 @@ impl FromAnyProto for ScreenInput {
                 }
             }
             Ok(Self { commands: acc })
-        } else {
-            Err(())
         }
     }
 
@@ -82,9 +88,17 @@ impl nodes::NodeInput for ScreenNode {
 }
 
 impl nodes::NodeOutput for ScreenNode {
-    fn op(&self, inputs: &mut Vec<Box<dyn Any>>) -> Result<Box<dyn Any>, ()> {
+    fn op(
+        &self,
+        inputs: &mut Vec<Box<dyn AnyClone>>,
+        _ctx: &nodes::EvalContext,
+    ) -> Result<Box<dyn AnyClone>, InputError> {
         nodes::FromAnyProto::from_any(nodes::InputStack::new(inputs, ..)).map(op)
     }
+
+    fn output_types(&self, _output: usize, _inputs: &[TypeSet]) -> TypeSet {
+        TypeSet::of::<nodes::One<Vec<Command>>>()
+    }
 }
 
 #[typetag::serde]
@@ -1,6 +1,8 @@
-use nodes::{FromAnyProto, InputStack, Node, NodeInput, NodeOutput, One, PossibleInputs};
+use nodes::{
+    AnyClone, FromAnyProto, InputError, InputStack, Node, NodeInput, NodeOutput, One,
+    PossibleInputs, TypeSet,
+};
 use solstice_2d::solstice::shader::RawUniformValue;
-use std::any::Any;
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
 enum UniformType {
@@ -14,23 +16,33 @@ enum UniformType {
     Vec4,
 }
 impl UniformType {
-    fn downcast(self, input: Box<dyn Any>) -> Result<RawUniformValue, Box<dyn Any>> {
-        fn downcast<T>(input: Box<dyn Any>) -> Result<RawUniformValue, Box<dyn Any>>
+    fn downcast(
+        self,
+        index: usize,
+        input: Box<dyn AnyClone>,
+    ) -> Result<RawUniformValue, InputError> {
+        fn downcast<T>(
+            index: usize,
+            input: Box<dyn AnyClone>,
+        ) -> Result<RawUniformValue, InputError>
         where
             T: Into<RawUniformValue> + 'static,
         {
-            input.downcast::<One<T>>().map(|v| v.inner().into())
+            input
+                .downcast::<One<T>>()
+                .map(|v| v.inner().into())
+                .map_err(|input| InputError::mismatch::<One<T>>(index, &*input))
         }
 
         match self {
-            UniformType::SignedInt => downcast::<i32>(input),
-            UniformType::Float => downcast::<f32>(input),
-            UniformType::Mat2 => downcast::<mint::ColumnMatrix2<f32>>(input),
-            UniformType::Mat3 => downcast::<mint::ColumnMatrix3<f32>>(input),
-            UniformType::Mat4 => downcast::<mint::ColumnMatrix4<f32>>(input),
-            UniformType::Vec2 => downcast::<mint::Vector2<f32>>(input),
-            UniformType::Vec3 => downcast::<mint::Vector3<f32>>(input),
-            UniformType::Vec4 => downcast::<mint::Vector4<f32>>(input),
+            UniformType::SignedInt => downcast::<i32>(index, input),
+            UniformType::Float => downcast::<f32>(index, input),
+            UniformType::Mat2 => downcast::<mint::ColumnMatrix2<f32>>(index, input),
+            UniformType::Mat3 => downcast::<mint::ColumnMatrix3<f32>>(index, input),
+            UniformType::Mat4 => downcast::<mint::ColumnMatrix4<f32>>(index, input),
+            UniformType::Vec2 => downcast::<mint::Vector2<f32>>(index, input),
+            UniformType::Vec3 => downcast::<mint::Vector3<f32>>(index, input),
+            UniformType::Vec4 => downcast::<mint::Vector4<f32>>(index, input),
         }
     }
 }
@@ -128,7 +140,7 @@ fn parse_uniforms(src: &str) -> impl Iterator<Item = Uniform> {
 type ShaderInput = crate::command::Shader;
 
 impl FromAnyProto for ShaderInput {
-    fn from_any(inputs: nodes::InputStack<'_, Box<dyn Any>>) -> Result<Self, ()> {
+    fn from_any(inputs: nodes::InputStack<'_, Box<dyn AnyClone>>) -> Result<Self, InputError> {
         if let Some(src) = inputs.as_slice().get(0) {
             if src.is::<One<String>>() {
                 let mut inputs = inputs.consume();
@@ -141,18 +153,22 @@ impl FromAnyProto for ShaderInput {
                 // TODO: need idempotent check first
                 let uniforms = parse_uniforms(&source)
                     .zip(inputs)
-                    .map(|(uniform, input)| {
-                        let v = uniform.ty.downcast(input)?;
+                    .enumerate()
+                    .map(|(index, (uniform, input))| {
+                        // the shader source is the first input
+                        let v = uniform.ty.downcast(index + 1, input)?;
                         Ok((uniform.name.to_owned(), v))
                     })
-                    .collect::<Result<_, Box<dyn Any>>>()
-                    .map_err(|_| ())?;
+                    .collect::<Result<_, InputError>>()?;
                 Ok(ShaderInput { source, uniforms })
             } else {
-                Err(())
+                Err(InputError::mismatch::<One<String>>(0, &**src))
             }
         } else {
-            Err(())
+            Err(InputError::ArityMismatch {
+                expected: 1,
+                found: 0,
+            })
         }
     }
 
@@ -161,7 +177,7 @@ impl FromAnyProto for ShaderInput {
     }
 }
 
-fn op(shader: ShaderInput) -> Box<dyn Any> {
+fn op(shader: ShaderInput) -> Box<dyn AnyClone> {
     Box::new(One::new(shader))
 }
 
@@ -211,13 +227,21 @@ impl NodeInput for ShaderNode {
 }
 
 impl NodeOutput for ShaderNode {
-    fn op(&self, inputs: &mut Vec<Box<dyn Any>>) -> Result<Box<dyn Any>, ()> {
+    fn op(
+        &self,
+        inputs: &mut Vec<Box<dyn AnyClone>>,
+        _ctx: &nodes::EvalContext,
+    ) -> Result<Box<dyn AnyClone>, InputError> {
         let r = ShaderInput::from_any(InputStack::new(inputs, ..));
         if let Ok(v) = &r {
             self.src.replace(Some(v.source.clone()));
         }
         r.map(op)
     }
+
+    fn output_types(&self, _output: usize, _inputs: &[TypeSet]) -> TypeSet {
+        TypeSet::of::<One<ShaderInput>>()
+    }
 }
 
 #[typetag::serde]
@@ -320,15 +344,15 @@ vec4 effect(vec4 color, Image texture, vec2 texture_coords, vec2 screen_coords)
     fn from_any_test() {
         let shader_node = ShaderNode::default();
 
-        let mut inputs: Vec<Box<dyn Any>> = vec![];
+        let mut inputs: Vec<Box<dyn AnyClone>> = vec![];
         inputs.push(Box::new(One::new(String::from(WITHOUT_UNIFORM))));
-        let shader_data = shader_node.op(&mut inputs).unwrap();
+        let shader_data = shader_node.op(&mut inputs, &Default::default()).unwrap();
         assert!(shader_data.downcast::<One<ShaderInput>>().is_ok());
 
         let color_uniform = mint::Vector4::from([0f32, 1., 2., 3.]);
         inputs.push(Box::new(One::new(String::from(WITH_UNIFORM))));
         inputs.push(Box::new(One::new(color_uniform)));
-        let shader_data = shader_node.op(&mut inputs).unwrap();
+        let shader_data = shader_node.op(&mut inputs, &Default::default()).unwrap();
         let shader_data = shader_data.downcast::<One<ShaderInput>>().unwrap();
         assert_eq!(shader_data.source, WITH_UNIFORM);
         assert_eq!(
@@ -346,9 +370,9 @@ vec4 effect(vec4 color, Image texture, vec2 texture_coords, vec2 screen_coords)
         assert_eq!(input_info.groups[0].info.len(), 1);
         assert_eq!(input_info.groups[0].info[0].name, "shader text");
 
-        let mut inputs: Vec<Box<dyn Any>> = vec![];
+        let mut inputs: Vec<Box<dyn AnyClone>> = vec![];
         inputs.push(Box::new(One::new(WITH_UNIFORM.to_owned())));
-        let output = shader_node.op(&mut inputs);
+        let output = shader_node.op(&mut inputs, &Default::default());
         assert!(output.is_ok());
 
         let input_info = shader_node.inputs();
@@ -358,7 +382,7 @@ vec4 effect(vec4 color, Image texture, vec2 texture_coords, vec2 screen_coords)
         assert_eq!(input_info.groups[0].info[1].name, "color");
 
         inputs.push(Box::new(One::new(WITHOUT_UNIFORM.to_owned())));
-        let output = shader_node.op(&mut inputs);
+        let output = shader_node.op(&mut inputs, &Default::default());
         assert!(output.is_ok());
 
         let input_info = shader_node.inputs();
@@ -369,6 +393,7 @@ vec4 effect(vec4 color, Image texture, vec2 texture_coords, vec2 screen_coords)
 
     #[test]
     fn any_cow() {
+        use std::any::Any;
         use std::borrow::Cow;
         let control = "test";
         let b: Box<dyn Any> = Box::new(Cow::<'static, str>::Owned(String::from(control)));
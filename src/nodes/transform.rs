@@ -1,8 +1,8 @@
 use nodes::{
-    FromAnyProto, InputStack, Node, NodeInput, NodeOutput, One, OneOrMany, PossibleInputs,
+    AnyClone, FromAnyProto, InputError, InputStack, Node, NodeInput, NodeOutput, One, OneOrMany,
+    PossibleInputs, TypeSet,
 };
 use solstice_2d::{Rad, Transform3D};
-use std::any::Any;
 
 #[derive(FromAnyProto, nodes::InputComponent)]
 struct TranslationInput {
@@ -12,7 +12,7 @@ struct TranslationInput {
 }
 
 impl TranslationInput {
-    fn op(self) -> Box<dyn Any> {
+    fn op(self) -> Box<dyn AnyClone> {
         let TranslationInput { x, y, z } = self;
         let x = x.unwrap_or(OneOrMany::One(One::new(0.)));
         let y = y.unwrap_or(OneOrMany::One(One::new(0.)));
@@ -30,7 +30,7 @@ struct EulerRotationInput {
 }
 
 impl EulerRotationInput {
-    fn op(self) -> Box<dyn Any> {
+    fn op(self) -> Box<dyn AnyClone> {
         let EulerRotationInput { roll, pitch, yaw } = self;
         let roll = roll.unwrap_or(OneOrMany::One(One::new(0.)));
         let pitch = pitch.unwrap_or(OneOrMany::One(One::new(0.)));
@@ -49,7 +49,7 @@ struct ScalingInput {
 }
 
 impl ScalingInput {
-    fn op(self) -> Box<dyn Any> {
+    fn op(self) -> Box<dyn AnyClone> {
         let ScalingInput { x, y, z } = self;
         let x = x.unwrap_or(OneOrMany::One(One::new(1.)));
         let y = y.unwrap_or(OneOrMany::One(One::new(1.)));
@@ -72,9 +72,17 @@ impl NodeInput for TranslationNode {
 }
 
 impl NodeOutput for TranslationNode {
-    fn op(&self, inputs: &mut Vec<Box<dyn Any>>) -> Result<Box<dyn Any>, ()> {
+    fn op(
+        &self,
+        inputs: &mut Vec<Box<dyn AnyClone>>,
+        _ctx: &nodes::EvalContext,
+    ) -> Result<Box<dyn AnyClone>, InputError> {
         FromAnyProto::from_any(InputStack::new(inputs, ..)).map(TranslationInput::op)
     }
+
+    fn output_types(&self, _output: usize, _inputs: &[TypeSet]) -> TypeSet {
+        TypeSet::one_or_many::<Transform3D>()
+    }
 }
 
 #[typetag::serde]
@@ -97,9 +105,17 @@ impl NodeInput for RotationNode {
 }
 
 impl NodeOutput for RotationNode {
-    fn op(&self, inputs: &mut Vec<Box<dyn Any>>) -> Result<Box<dyn Any>, ()> {
+    fn op(
+        &self,
+        inputs: &mut Vec<Box<dyn AnyClone>>,
+        _ctx: &nodes::EvalContext,
+    ) -> Result<Box<dyn AnyClone>, InputError> {
         FromAnyProto::from_any(InputStack::new(inputs, ..)).map(EulerRotationInput::op)
     }
+
+    fn output_types(&self, _output: usize, _inputs: &[TypeSet]) -> TypeSet {
+        TypeSet::one_or_many::<Transform3D>()
+    }
 }
 
 #[typetag::serde]
@@ -122,9 +138,17 @@ impl NodeInput for ScalingNode {
 }
 
 impl NodeOutput for ScalingNode {
-    fn op(&self, inputs: &mut Vec<Box<dyn Any>>) -> Result<Box<dyn Any>, ()> {
+    fn op(
+        &self,
+        inputs: &mut Vec<Box<dyn AnyClone>>,
+        _ctx: &nodes::EvalContext,
+    ) -> Result<Box<dyn AnyClone>, InputError> {
         FromAnyProto::from_any(InputStack::new(inputs, ..)).map(ScalingInput::op)
     }
+
+    fn output_types(&self, _output: usize, _inputs: &[TypeSet]) -> TypeSet {
+        TypeSet::one_or_many::<Transform3D>()
+    }
 }
 
 #[typetag::serde]